@@ -0,0 +1,31 @@
+use stm32f4xx_hal::pac;
+
+/// Number of RTC backup registers of the STM32F411
+pub const BACKUP_REGISTER_COUNT: usize = 20;
+
+/// First of the registers keeping the mold index of every DHT sensor
+pub const MOLD_INDEX: usize = 0;
+
+/// RTC backup registers keep their values over reset and standby as long
+/// as the backup domain is powered
+pub struct BackupRegisters {
+    rtc: pac::RTC,
+}
+
+impl BackupRegisters {
+    pub fn new(rtc: pac::RTC) -> Self {
+        // Clocks are already frozen, only the PWR clock enable bit is changed
+        let dp = unsafe { pac::Peripherals::steal() };
+        dp.RCC.apb1enr.modify(|_, w| w.pwren().set_bit());
+        dp.PWR.cr.modify(|_, w| w.dbp().set_bit());
+        Self { rtc }
+    }
+
+    pub fn read(&self, index: usize) -> u32 {
+        self.rtc.bkpr[index].read().bkp().bits()
+    }
+
+    pub fn write(&mut self, index: usize, value: u32) {
+        self.rtc.bkpr[index].write(|w| w.bkp().bits(value));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use embedded_hal::digital::v2::InputPin;
+use stm32f4xx_hal::gpio::{ErasedPin, Input};
+
+use crate::calibration::Button;
+
+/// Held button repeats after this delay
+const REPEAT_DELAY_MS: u32 = 600;
+const REPEAT_INTERVAL_MS: u32 = 150;
+
+/// Mode, Up and Down buttons connecting the pins to ground, polled while
+/// the main loop waits
+pub struct Buttons {
+    pins: [(Button, ErasedPin<Input>); 3],
+    /// Pressed button with the time until it repeats
+    held: Option<(Button, u32)>,
+}
+
+impl Buttons {
+    /// Pins have to be configured with pull-ups
+    pub fn new(mode: ErasedPin<Input>, up: ErasedPin<Input>, down: ErasedPin<Input>) -> Self {
+        Self {
+            pins: [(Button::Mode, mode), (Button::Up, up), (Button::Down, down)],
+            held: None,
+        }
+    }
+
+    /// Return the button pressed since the previous poll `elapsed_ms` ago or
+    /// a repeated Up/Down while it is held, only one button is recognized
+    pub fn poll(&mut self, elapsed_ms: u32) -> Option<Button> {
+        let pressed = self.pins.iter()
+            .find(|(_, pin)| pin.is_low().unwrap_or(false))
+            .map(|(button, _)| *button);
+
+        let (held, press) = match (self.held, pressed) {
+            (_, None) => (None, None),
+            (Some((previous, remaining_ms)), Some(button)) if previous == button => {
+                match remaining_ms.saturating_sub(elapsed_ms) {
+                    0 if button != Button::Mode => (Some((button, REPEAT_INTERVAL_MS)), Some(button)),
+                    remaining_ms => (Some((button, remaining_ms)), None),
+                }
+            },
+            (_, Some(button)) => (Some((button, REPEAT_DELAY_MS)), Some(button)),
+        };
+
+        self.held = held;
+        press
+    }
+}
\ No newline at end of file
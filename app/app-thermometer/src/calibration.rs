@@ -0,0 +1,292 @@
+use core::fmt::Display;
+use lib_calibration::{Calibration, CalibrationTable};
+
+use crate::config::{DHT_SENSOR_COUNT, MAX_SHT_SENSORS, SHT_SENSORS};
+use crate::sensors::{Measurement, TemperaturePressure, ShtKind, ShtMeasurement, SHT_ADDRESSES};
+
+/// Temperature and humidity of every DHT sensor, BMx280 temperature,
+/// pressure and humidity and temperature and humidity of every SHT address
+pub const CALIBRATION_CHANNEL_COUNT: usize = 2*DHT_SENSOR_COUNT + 3 + 2*MAX_SHT_SENSORS;
+
+pub type Calibrations = CalibrationTable<CALIBRATION_CHANNEL_COUNT>;
+
+// SHT channels are looked up by the address of the sensor
+const _: () = assert!(
+    sht_addresses_in_range(SHT_SENSORS),
+    "every configured SHT sensor needs an address in SHT_ADDRESSES"
+);
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum CalibrationChannel {
+    DhtTemperature(usize),
+    DhtHumidity(usize),
+    Bmx280Temperature,
+    Bmx280Pressure,
+    Bmx280Humidity,
+    /// SHT sensor with the I2C address, the calibration stays with the
+    /// address when other sensors are added or removed
+    ShtTemperature(u8),
+    ShtHumidity(u8),
+}
+
+impl CalibrationChannel {
+    pub fn index(&self) -> usize {
+        const BMX280: usize = 2*DHT_SENSOR_COUNT;
+        const SHT: usize = BMX280 + 3;
+
+        match *self {
+            CalibrationChannel::DhtTemperature(index) => 2*index,
+            CalibrationChannel::DhtHumidity(index) => 2*index + 1,
+            CalibrationChannel::Bmx280Temperature => BMX280,
+            CalibrationChannel::Bmx280Pressure => BMX280 + 1,
+            CalibrationChannel::Bmx280Humidity => BMX280 + 2,
+            CalibrationChannel::ShtTemperature(address) => SHT + 2*sht_slot(address),
+            CalibrationChannel::ShtHumidity(address) => SHT + 2*sht_slot(address) + 1,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        const BMX280: usize = 2*DHT_SENSOR_COUNT;
+        const SHT: usize = BMX280 + 3;
+
+        match index {
+            index if index < BMX280 && index % 2 == 0 => CalibrationChannel::DhtTemperature(index/2),
+            index if index < BMX280 => CalibrationChannel::DhtHumidity(index/2),
+            index if index == BMX280 => CalibrationChannel::Bmx280Temperature,
+            index if index == BMX280 + 1 => CalibrationChannel::Bmx280Pressure,
+            index if index == BMX280 + 2 => CalibrationChannel::Bmx280Humidity,
+            index if (index - SHT) % 2 == 0 => CalibrationChannel::ShtTemperature(sht_address(index - SHT)),
+            index => CalibrationChannel::ShtHumidity(sht_address(index - SHT)),
+        }
+    }
+
+    /// Number of decimal places of the channel values
+    pub fn decimals(&self) -> u32 {
+        match self {
+            CalibrationChannel::DhtTemperature(_)
+            | CalibrationChannel::DhtHumidity(_)
+            | CalibrationChannel::Bmx280Humidity => 1,
+            // Pressure in pascals is shown in hPa
+            CalibrationChannel::Bmx280Temperature
+            | CalibrationChannel::Bmx280Pressure
+            | CalibrationChannel::ShtTemperature(_)
+            | CalibrationChannel::ShtHumidity(_) => 2,
+        }
+    }
+
+    /// Reference value change per button press, 0.1 of the unit
+    fn step(&self) -> i32 {
+        10_i32.pow(self.decimals() - 1)
+    }
+}
+
+impl Display for CalibrationChannel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CalibrationChannel::DhtTemperature(index) => write!(f, "DHT{}T", index + 1),
+            CalibrationChannel::DhtHumidity(index) => write!(f, "DHT{}H", index + 1),
+            CalibrationChannel::Bmx280Temperature => write!(f, "BMXT"),
+            CalibrationChannel::Bmx280Pressure => write!(f, "BMXP"),
+            CalibrationChannel::Bmx280Humidity => write!(f, "BMXH"),
+            CalibrationChannel::ShtTemperature(address) => write!(f, "SHT{:02X}T", address),
+            CalibrationChannel::ShtHumidity(address) => write!(f, "SHT{:02X}H", address),
+        }
+    }
+}
+
+fn sht_slot(address: u8) -> usize {
+    (address - SHT_ADDRESSES.start) as usize
+}
+
+const fn sht_addresses_in_range(sensors: &[(ShtKind, u8)]) -> bool {
+    let mut index = 0;
+
+    while index < sensors.len() {
+        let address = sensors[index].1;
+
+        if address < SHT_ADDRESSES.start || address >= SHT_ADDRESSES.end {
+            return false;
+        }
+
+        index += 1;
+    }
+
+    true
+}
+
+fn sht_address(offset: usize) -> u8 {
+    SHT_ADDRESSES.start + (offset/2) as u8
+}
+
+/// Uncalibrated values of the latest reading
+#[derive(Copy, Clone)]
+pub struct RawValues {
+    values: [Option<i32>; CALIBRATION_CHANNEL_COUNT],
+}
+
+impl RawValues {
+    pub fn new() -> Self {
+        Self { values: [None; CALIBRATION_CHANNEL_COUNT] }
+    }
+
+    pub fn get(&self, channel: CalibrationChannel) -> Option<i32> {
+        self.values[channel.index()]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (CalibrationChannel, i32)> + '_ {
+        self.values.iter().enumerate()
+            .filter_map(|(index, value)| value.map(|value| (CalibrationChannel::from_index(index), value)))
+    }
+
+    /// Remember the raw value and return the calibrated one
+    fn calibrate(&mut self, calibrations: &Calibrations, channel: CalibrationChannel, raw: i32) -> i32 {
+        self.values[channel.index()] = Some(raw);
+        calibrations.apply(channel.index(), raw)
+    }
+
+    pub fn calibrate_dht(
+        &mut self,
+        calibrations: &Calibrations,
+        index: usize,
+        values: Measurement,
+    ) -> Measurement {
+        let temperature = CalibrationChannel::DhtTemperature(index);
+        let humidity = CalibrationChannel::DhtHumidity(index);
+
+        Measurement {
+            temperature: self.calibrate(calibrations, temperature, values.temperature as i32)
+                .clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+            humidity: self.calibrate(calibrations, humidity, values.humidity as i32)
+                .clamp(0, 1000) as u16,
+        }
+    }
+
+    pub fn calibrate_bmx280(
+        &mut self,
+        calibrations: &Calibrations,
+        values: TemperaturePressure,
+    ) -> TemperaturePressure {
+        let humidity = values.humidity.map(|humidity| {
+            self.calibrate(calibrations, CalibrationChannel::Bmx280Humidity, humidity as i32)
+                .clamp(0, 1000) as u16
+        });
+
+        TemperaturePressure {
+            temperature: self.calibrate(calibrations, CalibrationChannel::Bmx280Temperature, values.temperature),
+            pressure: self.calibrate(calibrations, CalibrationChannel::Bmx280Pressure, values.pressure),
+            humidity,
+        }
+    }
+
+    pub fn calibrate_sht(
+        &mut self,
+        calibrations: &Calibrations,
+        address: u8,
+        values: ShtMeasurement,
+    ) -> ShtMeasurement {
+        let temperature = CalibrationChannel::ShtTemperature(address);
+        let humidity = CalibrationChannel::ShtHumidity(address);
+
+        ShtMeasurement {
+            temperature: self.calibrate(calibrations, temperature, values.temperature),
+            humidity: self.calibrate(calibrations, humidity, values.humidity as i32)
+                .clamp(0, 10000) as u16,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Button {
+    Mode,
+    Up,
+    Down,
+}
+
+/// Step of the two-point calibration shown on the display
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum CalibrationStep {
+    Idle,
+    /// Up/Down choose the channel, index `CALIBRATION_CHANNEL_COUNT` leaves
+    Select(usize),
+    /// Up/Down set the reference value, Mode takes the current raw value,
+    /// the first point is kept while the second one is being set
+    Point {
+        channel: CalibrationChannel,
+        first: Option<(i32, i32)>,
+        reference: i32,
+    },
+    Finished(CalibrationChannel, bool),
+}
+
+/// Two-point calibration of a single channel, for each point the reference
+/// value (from a reference instrument) is set by buttons and stored with
+/// the raw value measured at the moment Mode is pressed
+pub struct CalibrationProcedure {
+    step: CalibrationStep,
+}
+
+impl CalibrationProcedure {
+    pub fn new() -> Self {
+        Self { step: CalibrationStep::Idle }
+    }
+
+    pub fn step(&self) -> CalibrationStep {
+        self.step
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.step != CalibrationStep::Idle
+    }
+
+    /// React to a pressed button, true is returned when the calibration
+    /// of a channel was changed and should be stored
+    pub fn press(
+        &mut self,
+        button: Button,
+        raw_values: &RawValues,
+        calibrations: &mut Calibrations,
+    ) -> bool {
+        let mut changed = false;
+
+        self.step = match (self.step, button) {
+            (CalibrationStep::Idle, Button::Mode) => CalibrationStep::Select(0),
+            (CalibrationStep::Idle, _) => CalibrationStep::Idle,
+            (CalibrationStep::Select(index), Button::Up) =>
+                CalibrationStep::Select((index + 1) % (CALIBRATION_CHANNEL_COUNT + 1)),
+            (CalibrationStep::Select(index), Button::Down) =>
+                CalibrationStep::Select((index + CALIBRATION_CHANNEL_COUNT) % (CALIBRATION_CHANNEL_COUNT + 1)),
+            (CalibrationStep::Select(CALIBRATION_CHANNEL_COUNT), Button::Mode) => CalibrationStep::Idle,
+            (CalibrationStep::Select(index), Button::Mode) => {
+                let channel = CalibrationChannel::from_index(index);
+                let reference = raw_values.get(channel)
+                    .map_or(0, |raw| calibrations.apply(channel.index(), raw));
+
+                CalibrationStep::Point { channel, first: None, reference }
+            },
+            (CalibrationStep::Point { channel, first, reference }, Button::Up) =>
+                CalibrationStep::Point { channel, first, reference: reference + channel.step() },
+            (CalibrationStep::Point { channel, first, reference }, Button::Down) =>
+                CalibrationStep::Point { channel, first, reference: reference - channel.step() },
+            (CalibrationStep::Point { channel, first, reference }, Button::Mode) => {
+                match (raw_values.get(channel), first) {
+                    (None, _) => self.step,
+                    (Some(raw), None) =>
+                        CalibrationStep::Point { channel, first: Some((raw, reference)), reference },
+                    (Some(raw), Some(first)) => {
+                        match Calibration::two_point(first, (raw, reference)) {
+                            Some(calibration) => {
+                                calibrations.set(channel.index(), calibration);
+                                changed = true;
+                                CalibrationStep::Finished(channel, true)
+                            },
+                            None => CalibrationStep::Finished(channel, false),
+                        }
+                    },
+                }
+            },
+            (CalibrationStep::Finished(..), _) => CalibrationStep::Idle,
+        };
+
+        changed
+    }
+}
\ No newline at end of file
@@ -0,0 +1,272 @@
+use lib_onewire::ds18b20::Resolution;
+use lib_ntc::{NtcConfig, NtcModel, Divider};
+use lib_health::{ChannelConfig, Limits};
+use lib_filter::{FilterConfig, SpikeConfig};
+use crate::metrics::{Metric, Metrics};
+use crate::ventilation::VentilationConfig;
+use crate::sensors::{
+    DhtKind, DhtChannel, ShtKind, SHT_ADDRESS_DEFAULT, RtdConfig, Pms5003Config, Pms5003Mode,
+    SupplyConfig, VoltageDivider, Bmx280Profile, BMX280_ADDRESS_SDO_GROUNDED
+};
+
+/// DHT temperature/humidity sensors connected to the board, their data pins
+/// are listed in the same order in `main`, all other per-sensor settings and
+/// state follow the length of this table
+pub const DHT_CHANNELS: [DhtChannel; 6] = [
+    DhtChannel { kind: DhtKind::Dht11, mold: true },
+    DhtChannel { kind: DhtKind::Dht11, mold: true },
+    DhtChannel { kind: DhtKind::Dht11, mold: true },
+    DhtChannel { kind: DhtKind::Dht11, mold: true },
+    DhtChannel { kind: DhtKind::Dht11, mold: true },
+    // Outside air, the mold index is not tracked
+    DhtChannel { kind: DhtKind::Dht11, mold: false },
+];
+
+/// Number of DHT temperature/humidity sensors connected to the board
+pub const DHT_SENSOR_COUNT: usize = DHT_CHANNELS.len();
+
+/// Seconds the last good DHT reading is shown and logged as stale after
+/// a failed one, then the sensor counts as missing
+pub const DHT_STALE_SECONDS: u32 = 60;
+
+/// Maximum number of DS18B20 probes registered on the 1-Wire bus
+pub const MAX_DS18B20_PROBES: usize = 8;
+
+/// Resolution of all DS18B20 probes, conversion has to finish within
+/// a single measurement loop
+pub const DS18B20_RESOLUTION: Resolution = Resolution::Bits11;
+
+/// PT100 with 430 ohm reference resistor, as on the common MAX31865 boards
+pub const RTD_CONFIG: RtdConfig = RtdConfig {
+    nominal_ohms: 100,
+    reference_ohms: 430,
+    three_wire: false,
+    filter_50hz: true,
+};
+
+/// Number of NTC thermistors connected to the ADC pins
+pub const NTC_PROBE_COUNT: usize = 2;
+
+/// Divider and thermistor model of every NTC probe
+pub const NTC_PROBES: [NtcConfig; NTC_PROBE_COUNT] = [
+    // Generic 10k B3950 probe
+    NtcConfig {
+        series_ohms: 10_000,
+        divider: Divider::NtcToGround,
+        model: NtcModel::Beta { beta: 3950, nominal_ohms: 10_000, nominal_temperature: 2500 },
+    },
+    // 10k probe with coefficients from the manufacturer table
+    NtcConfig {
+        series_ohms: 10_000,
+        divider: Divider::NtcToGround,
+        model: NtcModel::SteinhartHart {
+            a: 1_129_148_000_000,
+            b: 234_125_000_000,
+            c: 87_674_100,
+        },
+    },
+];
+
+/// Number of ADC conversions summed into a single NTC reading
+pub const NTC_OVERSAMPLING: u32 = 16;
+
+/// Maximum number of temperature-only probe channels of all kinds,
+/// DS18B20 probes, thermocouple, RTD and thermistors
+pub const MAX_TEMPERATURE_PROBES: usize = MAX_DS18B20_PROBES + 2 + NTC_PROBE_COUNT;
+
+/// Kind and I2C address of every SHT3x/SHT4x sensor on the I2C bus, used
+/// when the startup scan finds none
+pub const SHT_SENSORS: &[(ShtKind, u8)] = &[
+    (ShtKind::Sht3x, SHT_ADDRESS_DEFAULT),
+];
+
+/// Maximum number of SHT3x/SHT4x sensors, one per address 0x44 - 0x47
+pub const MAX_SHT_SENSORS: usize = 4;
+
+/// Relative humidity (in 1/100 percent) above which the SHT heater is
+/// pulsed after every measurement to recover from condensation
+pub const SHT_HEATER_HUMIDITY: u16 = 9500;
+
+/// Automatic self calibration of the SCD4x assumes the sensor sees fresh
+/// air (around 400 ppm) at least once a week
+pub const SCD4X_AUTOMATIC_SELF_CALIBRATION: bool = true;
+
+/// CO2 concentration in ppm at which the alarm is shown and logged
+pub const CO2_ALARM_PPM: u16 = 1400;
+
+/// PMS5003 is read on request every loop iteration by default, set `cycle`
+/// to run the sensor only for `warmup` iterations out of every `cycle`
+pub const PMS5003_CONFIG: Pms5003Config = Pms5003Config {
+    mode: Pms5003Mode::Passive,
+    timeout_ms: 100,
+    cycle: 0,
+    warmup: 30,
+};
+
+/// Single Li-ion cell measured through a 100k/100k divider on PB1, the
+/// logger shuts down before the cell protection cuts the power
+pub const SUPPLY_CONFIG: SupplyConfig = SupplyConfig {
+    battery_divider: Some(VoltageDivider { top_ohms: 100_000, bottom_ohms: 100_000 }),
+    empty_millivolts: 3400,
+    full_millivolts: 4150,
+    low_readings: 5,
+};
+
+/// Number of ADC conversions summed into a single supply or battery reading
+pub const SUPPLY_OVERSAMPLING: u32 = 16;
+
+/// I2C address of the BMP280/BME280 pressure sensor, used when the
+/// startup scan does not find it
+pub const BMX280_ADDRESS: u8 = BMX280_ADDRESS_SDO_GROUNDED;
+
+/// Pressure changes slowly at a wall weather station, a single forced
+/// measurement per reading keeps the sensor from heating itself
+pub const BMX280_PROFILE: Bmx280Profile = Bmx280Profile::WEATHER_MONITORING;
+
+/// Altitude of the station above the sea level in meters, used to reduce
+/// the measured pressure to the sea level
+pub const STATION_ALTITUDE: i32 = 0;
+
+/// Pressure is recorded every 10 minutes, 19 samples cover the 3 hours
+/// of the pressure tendency
+pub const PRESSURE_HISTORY_INTERVAL_MINUTES: u32 = 10;
+pub const PRESSURE_HISTORY_LENGTH: usize = 19;
+
+/// Values derived from every DHT measurement shown on the display, dew
+/// point tells whether the walls of a cellar are going to get wet
+pub const DHT_DISPLAY_METRICS: [Metrics; DHT_SENSOR_COUNT] =
+    [Metrics::NONE.with(Metric::DewPoint); DHT_SENSOR_COUNT];
+
+/// Values derived from every DHT measurement written to the log
+pub const DHT_LOG_METRICS: [Metrics; DHT_SENSOR_COUNT] =
+    [Metrics::NONE.with(Metric::DewPoint).with(Metric::AbsoluteHumidity); DHT_SENSOR_COUNT];
+
+/// Cellar measured by the first DHT sensor, outside air by the last one,
+/// ventilation starts when the outside air is drier by 1 g/m3
+pub const VENTILATION: Option<VentilationConfig> = Some(VentilationConfig {
+    indoor: 0,
+    outdoor: DHT_SENSOR_COUNT - 1,
+    start_difference: 100,
+    stop_difference: 50,
+    condensation_margin: 200,
+    minimum_on_minutes: 15,
+    minimum_off_minutes: 10,
+});
+
+/// Fan relay is connected to PB2 and follows the ventilation advice
+pub const VENTILATION_FAN_RELAY: bool = false;
+
+/// Plausibility of DHT temperature and humidity (both in 1/10), DHT22 range,
+/// the stuck check needs both values unchanged
+pub const DHT_HEALTH: ChannelConfig<2> = ChannelConfig {
+    limits: [
+        Limits { minimum: -400, maximum: 800, max_rate: 50 },
+        Limits { minimum: 0, maximum: 1000, max_rate: 200 },
+    ],
+    stuck_seconds: 6*3600,
+};
+
+/// Plausibility of BMx280 temperature (1/100 degrees) and pressure (Pa)
+pub const BMX280_HEALTH: ChannelConfig<2> = ChannelConfig {
+    limits: [
+        Limits { minimum: -4000, maximum: 8500, max_rate: 500 },
+        Limits { minimum: 30000, maximum: 110000, max_rate: 200 },
+    ],
+    stuck_seconds: 3600,
+};
+
+/// Plausibility of SCD4x CO2 in ppm
+pub const CO2_HEALTH: ChannelConfig<1> = ChannelConfig {
+    limits: [Limits { minimum: 300, maximum: 40000, max_rate: 2000 }],
+    stuck_seconds: 3600,
+};
+
+/// Plausibility of PMS5003 PM2.5 in ug/m3, clean air reads 0 for hours and
+/// smoke changes it arbitrarily fast, so only the range is checked
+pub const PARTICULATE_HEALTH: ChannelConfig<1> = ChannelConfig {
+    limits: [Limits { minimum: 0, maximum: 1000, max_rate: 0 }],
+    stuck_seconds: 0,
+};
+
+/// Plausibility of DS18B20 temperature in 1/100 degrees, probes may sit
+/// in a medium with a constant temperature, so they are never stuck
+pub const DS18B20_HEALTH: ChannelConfig<1> = ChannelConfig {
+    limits: [Limits { minimum: -5500, maximum: 12500, max_rate: 1000 }],
+    stuck_seconds: 0,
+};
+
+/// Plausibility of type K thermocouple temperature in 1/100 degrees
+pub const THERMOCOUPLE_HEALTH: ChannelConfig<1> = ChannelConfig {
+    limits: [Limits { minimum: -27000, maximum: 137200, max_rate: 0 }],
+    stuck_seconds: 0,
+};
+
+/// Plausibility of PT100/PT1000 temperature in 1/100 degrees
+pub const RTD_HEALTH: ChannelConfig<1> = ChannelConfig {
+    limits: [Limits { minimum: -20000, maximum: 85000, max_rate: 0 }],
+    stuck_seconds: 0,
+};
+
+/// Plausibility of thermistor temperature in 1/100 degrees
+pub const NTC_HEALTH: ChannelConfig<1> = ChannelConfig {
+    limits: [Limits { minimum: -4000, maximum: 12500, max_rate: 1000 }],
+    stuck_seconds: 0,
+};
+
+/// Plausibility of SHT temperature and humidity (both in 1/100)
+pub const SHT_HEALTH: ChannelConfig<2> = ChannelConfig {
+    limits: [
+        Limits { minimum: -4000, maximum: 12500, max_rate: 500 },
+        Limits { minimum: 0, maximum: 10000, max_rate: 2000 },
+    ],
+    stuck_seconds: 6*3600,
+};
+
+/// Plausibility of the analog supply in millivolts, the STM32F411 operating
+/// range, a stable supply never changes
+pub const SUPPLY_HEALTH: ChannelConfig<1> = ChannelConfig {
+    limits: [Limits { minimum: 1700, maximum: 3600, max_rate: 0 }],
+    stuck_seconds: 0,
+};
+
+/// Log the values before calibration next to the calibrated ones
+pub const LOG_RAW_VALUES: bool = false;
+
+/// Values the median filters choose from
+pub const FILTER_MEDIAN_LENGTH: usize = 5;
+
+/// DHT11 temperature (1/10 degrees) has 1 degree resolution, the average
+/// hides the steps
+pub const DHT_TEMPERATURE_FILTER: FilterConfig = FilterConfig {
+    spike: Some(SpikeConfig { max_jump: 50, max_rejections: 3 }),
+    median: true,
+    ema: Some(20),
+};
+
+/// DHT humidity in 1/10 percent
+pub const DHT_HUMIDITY_FILTER: FilterConfig = FilterConfig {
+    spike: Some(SpikeConfig { max_jump: 150, max_rejections: 3 }),
+    median: true,
+    ema: Some(20),
+};
+
+pub const BMX280_TEMPERATURE_FILTER: FilterConfig = FilterConfig {
+    spike: None,
+    median: false,
+    ema: Some(30),
+};
+
+/// BMP280 pressure in pascals, the sensor IIR filter is configured by the profile
+pub const BMX280_PRESSURE_FILTER: FilterConfig = FilterConfig {
+    spike: Some(SpikeConfig { max_jump: 200, max_rejections: 3 }),
+    median: true,
+    ema: Some(30),
+};
+
+pub const BMX280_HUMIDITY_FILTER: FilterConfig = FilterConfig::NONE;
+pub const SHT_TEMPERATURE_FILTER: FilterConfig = FilterConfig::NONE;
+pub const SHT_HUMIDITY_FILTER: FilterConfig = FilterConfig::NONE;
+
+/// Log the filtered values shown on the display, otherwise the values
+/// as they were read (after calibration)
+pub const LOG_FILTERED_VALUES: bool = true;
\ No newline at end of file
@@ -3,25 +3,65 @@ use arrayvec::ArrayString;
 use embedded_graphics::{
     pixelcolor::BinaryColor,
     prelude::*,
-    mono_font::{MonoTextStyle, ascii::{FONT_5X8}}, text::{Text}
+    mono_font::{MonoTextStyle, ascii::{FONT_5X8}}, text::{Text},
+    primitives::{Rectangle, PrimitiveStyle}
 };
-use crate::format::{format_sensors_display};
+use crate::format::{format_sensors_display, display_page_count, format_calibration};
+use crate::calibration::{CalibrationStep, Calibrations};
 use hx1230::{ArrayDisplayBuffer, DisplayBuffer, DisplayDriver};
 
-use crate::sensors::Sensors;
+use crate::sensors::{Sensors, SupplyVoltage};
 
 pub fn render_display(
     buffer: &mut ArrayDisplayBuffer,
     driver: &mut dyn DisplayDriver,
     sd_result: &str,
-    sensors: &Sensors
+    sensors: &Sensors,
+    page: usize,
 ) {
     buffer.clear_buffer(0x00);
     let mut text = ArrayString::<200>::new();
     let _ = writeln!(&mut text, "{}", sd_result);
-    format_sensors_display(&mut text, &sensors);
+    format_sensors_display(&mut text, &sensors, page % display_page_count(sensors));
     let style = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
     let position = Point::new(0, 8);
     let _ = Text::new(&text, position, style).draw(buffer);
+    draw_battery_icon(buffer, &sensors.supply);
     let _ = driver.send_buffer(buffer);
+}
+
+/// Show the calibration procedure instead of the sensors
+pub fn render_calibration(
+    buffer: &mut ArrayDisplayBuffer,
+    driver: &mut dyn DisplayDriver,
+    step: CalibrationStep,
+    sensors: &Sensors,
+    calibrations: &Calibrations,
+) {
+    let mut text = ArrayString::<160>::new();
+    format_calibration(&mut text, step, &sensors.raw, calibrations);
+    render_message(buffer, driver, &text);
+}
+
+/// Show a single message, used before the logger shuts down
+pub fn render_message(
+    buffer: &mut ArrayDisplayBuffer,
+    driver: &mut dyn DisplayDriver,
+    message: &str,
+) {
+    buffer.clear_buffer(0x00);
+    let style = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
+    let _ = Text::new(message, Point::new(0, 8), style).draw(buffer);
+    let _ = driver.send_buffer(buffer);
+}
+
+/// Battery outline in the top right corner (next to the SD card status)
+/// filled according to the charge percentage
+fn draw_battery_icon(buffer: &mut ArrayDisplayBuffer, supply: &SupplyVoltage) {
+    let outline = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+    let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+    let _ = Rectangle::new(Point::new(84, 0), Size::new(11, 7)).into_styled(outline).draw(buffer);
+    let _ = Rectangle::new(Point::new(95, 2), Size::new(1, 3)).into_styled(fill).draw(buffer);
+    let width = (supply.percentage as u32*9 + 50)/100;
+    let _ = Rectangle::new(Point::new(85, 1), Size::new(width, 5)).into_styled(fill).draw(buffer);
 }
\ No newline at end of file
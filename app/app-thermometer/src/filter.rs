@@ -0,0 +1,124 @@
+use lib_filter::{Filter, FilterConfig};
+use lib_weather::psychrometrics;
+
+use crate::calibration::{CalibrationChannel, CALIBRATION_CHANNEL_COUNT};
+use crate::config::{
+    FILTER_MEDIAN_LENGTH, DHT_TEMPERATURE_FILTER, DHT_HUMIDITY_FILTER, BMX280_TEMPERATURE_FILTER,
+    BMX280_PRESSURE_FILTER, BMX280_HUMIDITY_FILTER, SHT_TEMPERATURE_FILTER, SHT_HUMIDITY_FILTER
+};
+use crate::sensors::Sensors;
+
+/// Filters of the temperature, humidity and pressure channels, the same
+/// channels as are calibrated
+pub struct SensorFilters {
+    filters: [Filter<FILTER_MEDIAN_LENGTH>; CALIBRATION_CHANNEL_COUNT],
+    /// Channels with a value in the last `apply`
+    updated: [bool; CALIBRATION_CHANNEL_COUNT],
+    /// Latest output of every filter, shown again for held values
+    outputs: [Option<i32>; CALIBRATION_CHANNEL_COUNT],
+}
+
+impl SensorFilters {
+    pub fn new() -> Self {
+        Self {
+            filters: core::array::from_fn(|index| {
+                Filter::new(filter_config(CalibrationChannel::from_index(index)))
+            }),
+            updated: [false; CALIBRATION_CHANNEL_COUNT],
+            outputs: [None; CALIBRATION_CHANNEL_COUNT],
+        }
+    }
+
+    /// Copy of the sensors with filtered values, stale DHT values are not
+    /// fed to the filters again but replaced by their latest output and the
+    /// derived DHT values follow the filtered ones. Filters of missing
+    /// channels are reset, so that the history of a sensor that disappeared
+    /// is not applied when it returns.
+    pub fn apply(&mut self, sensors: &Sensors) -> Sensors {
+        let mut filtered = sensors.clone();
+        self.updated = [false; CALIBRATION_CHANNEL_COUNT];
+
+        if let Some(values) = filtered.temperature_pressure.as_mut() {
+            values.temperature = self.update(CalibrationChannel::Bmx280Temperature, values.temperature);
+            values.pressure = self.update(CalibrationChannel::Bmx280Pressure, values.pressure);
+            values.humidity = values.humidity.map(|humidity| {
+                self.update(CalibrationChannel::Bmx280Humidity, humidity as i32).clamp(0, 1000) as u16
+            });
+        }
+
+        for reading in filtered.sht_sensors.iter_mut() {
+            if let Ok(values) = reading.measurement.as_mut() {
+                let temperature = CalibrationChannel::ShtTemperature(reading.address);
+                let humidity = CalibrationChannel::ShtHumidity(reading.address);
+
+                values.temperature = self.update(temperature, values.temperature);
+                values.humidity = self.update(humidity, values.humidity as i32)
+                    .clamp(0, 10000) as u16;
+            }
+        }
+
+        for (index, reading) in filtered.temperature_humidity.iter_mut().enumerate() {
+            if let Ok(held) = reading {
+                let temperature = CalibrationChannel::DhtTemperature(index);
+                let humidity = CalibrationChannel::DhtHumidity(index);
+
+                let raw = held.value;
+
+                // Filter state is kept while the last good value is held
+                // and its latest output is shown instead of the raw value
+                let (temperature, humidity) = match held.is_stale() {
+                    true => (
+                        self.hold(temperature, raw.temperature as i32),
+                        self.hold(humidity, raw.humidity as i32),
+                    ),
+                    false => (
+                        self.update(temperature, raw.temperature as i32),
+                        self.update(humidity, raw.humidity as i32),
+                    ),
+                };
+
+                held.value.temperature = temperature.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+                held.value.humidity = humidity.clamp(0, 1000) as u16;
+
+                filtered.temperature_humidity_derived[index] = Some(psychrometrics(
+                    held.value.temperature as i32*10,
+                    held.value.humidity as u32*10,
+                ));
+            }
+        }
+
+        for (index, filter) in self.filters.iter_mut().enumerate() {
+            if !self.updated[index] {
+                filter.reset();
+                self.outputs[index] = None;
+            }
+        }
+
+        filtered
+    }
+
+    fn update(&mut self, channel: CalibrationChannel, value: i32) -> i32 {
+        let output = self.filters[channel.index()].update(value);
+        self.updated[channel.index()] = true;
+        self.outputs[channel.index()] = Some(output);
+        output
+    }
+
+    /// Latest output of the channel without updating its filter
+    fn hold(&mut self, channel: CalibrationChannel, value: i32) -> i32 {
+        self.updated[channel.index()] = true;
+        self.outputs[channel.index()].unwrap_or(value)
+    }
+}
+
+fn filter_config(channel: CalibrationChannel) -> FilterConfig {
+    match channel {
+        CalibrationChannel::DhtTemperature(_) => DHT_TEMPERATURE_FILTER,
+        CalibrationChannel::DhtHumidity(_) => DHT_HUMIDITY_FILTER,
+        CalibrationChannel::Bmx280Temperature => BMX280_TEMPERATURE_FILTER,
+        CalibrationChannel::Bmx280Pressure => BMX280_PRESSURE_FILTER,
+        CalibrationChannel::Bmx280Humidity => BMX280_HUMIDITY_FILTER,
+        CalibrationChannel::ShtTemperature(_) => SHT_TEMPERATURE_FILTER,
+        CalibrationChannel::ShtHumidity(_) => SHT_HUMIDITY_FILTER,
+    }
+}
\ No newline at end of file
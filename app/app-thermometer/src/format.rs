@@ -1,10 +1,145 @@
 use core::fmt::Write;
-use dht11::Measurement;
 use embedded_sdmmc::SdMmcError;
 use lib_datalogger::DatalogError;
 use pcf8563::DateTime;
+use lib_weather::{PressureTendency, Trend, Forecast};
 
-use crate::sensors::{Sensors, TemperaturePressure};
+use crate::config::{DHT_DISPLAY_METRICS, VENTILATION};
+use crate::metrics::Metric;
+use crate::ventilation::{Ventilation, Advice};
+use crate::mold::MoldRisk;
+use crate::health::SensorHealth;
+use lib_health::{ChannelHealth, Held};
+use crate::calibration::{
+    Calibrations, CalibrationChannel, CalibrationStep, RawValues, CALIBRATION_CHANNEL_COUNT
+};
+use crate::sensors::{
+    Sensors, TemperaturePressure, Measurement, DhtError, TemperatureProbe, ProbeSource,
+    ShtReading, Co2Measurement, ParticulateMatter, SupplyVoltage, I2cScan
+};
+
+/// Number of sensor lines that fit on the display below the SD card
+/// status, time and BMP280 lines
+pub const DISPLAY_SENSOR_LINES: usize = 4;
+
+/// Number of characters on a display line, 96 pixels of 5 pixel wide font
+const DISPLAY_LINE_WIDTH: usize = 19;
+
+/// Diagnostics pages replace the date and BMP280/BME280 lines by a title,
+/// leaving one more line for the channels
+const DIAGNOSTICS_LINES: usize = DISPLAY_SENSOR_LINES + 1;
+
+/// Number of display pages needed to show all sensor lines followed by
+/// the diagnostics
+pub fn display_page_count(sensors: &Sensors) -> usize {
+    sensor_page_count(sensors) + diagnostics_page_count(&sensors.health)
+}
+
+fn sensor_page_count(sensors: &Sensors) -> usize {
+    let lines = sensor_lines(sensors).count();
+    ((lines + DISPLAY_SENSOR_LINES - 1) / DISPLAY_SENSOR_LINES).max(1)
+}
+
+fn diagnostics_page_count(health: &SensorHealth) -> usize {
+    (health.iter().count() + DIAGNOSTICS_LINES - 1) / DIAGNOSTICS_LINES
+}
+
+/// Single line on the paged part of the display
+enum SensorLine<'a> {
+    Ventilation(Option<&'a Ventilation>),
+    SeaLevelPressure(i32, Option<&'a PressureTendency>),
+    Forecast(&'a Forecast),
+    Text(&'a str),
+    Bme280Humidity(u16),
+    Co2(Option<&'a Co2Measurement>),
+    ParticulateMatter(Option<&'a ParticulateMatter>),
+    Sht(&'a ShtReading),
+    Dht(usize, Result<&'a Held<Measurement>, &'a DhtError>),
+    /// Value of the metric derived from a DHT measurement
+    Derived(usize, Metric, i32),
+    Mold(usize, &'a MoldRisk),
+    Probe(&'a TemperatureProbe),
+    Supply(&'a SupplyVoltage),
+}
+
+/// Ventilation advice (when configured), sea level pressure, the forecast and
+/// BME280 humidity are listed first (the pressure line is already full),
+/// followed by CO2, particulate matter, SHT sensors, DHT sensors, temperature
+/// probes and the supply voltage
+fn sensor_lines(sensors: &Sensors) -> impl Iterator<Item = SensorLine<'_>> {
+    let bme280_humidity = sensors.temperature_pressure.as_ref()
+        .and_then(|values| values.humidity)
+        .map(SensorLine::Bme280Humidity);
+
+    let sea_level_pressure = sensors.sea_level_pressure
+        .map(|pressure| SensorLine::SeaLevelPressure(pressure, sensors.pressure_tendency.as_ref()));
+
+    let forecast = sensors.forecast.iter().flat_map(|forecast| {
+        core::iter::once(SensorLine::Forecast(forecast))
+            .chain(WordWrap::new(forecast.text(), DISPLAY_LINE_WIDTH).map(SensorLine::Text))
+    });
+
+    let ventilation = VENTILATION.as_ref()
+        .map(|_| SensorLine::Ventilation(sensors.ventilation.as_ref()));
+
+    ventilation.into_iter()
+        .chain(sea_level_pressure)
+        .chain(forecast)
+        .chain(bme280_humidity)
+        .chain(core::iter::once(SensorLine::Co2(sensors.co2.as_ref())))
+        .chain(core::iter::once(SensorLine::ParticulateMatter(sensors.particulate_matter.as_ref())))
+        .chain(sensors.sht_sensors.iter().map(SensorLine::Sht))
+        .chain(sensors.temperature_humidity.iter().enumerate().flat_map(|(index, values)| {
+            let derived = sensors.temperature_humidity_derived[index];
+
+            let derived_lines = DHT_DISPLAY_METRICS[index].iter()
+                .filter_map(move |metric| derived.as_ref()
+                    .map(|values| SensorLine::Derived(index, metric, metric.value(values))));
+
+            let mold_line = sensors.mold[index].as_ref().map(|risk| SensorLine::Mold(index, risk));
+
+            core::iter::once(SensorLine::Dht(index, values.as_ref()))
+                .chain(derived_lines)
+                .chain(mold_line)
+        }))
+        .chain(sensors.temperature_probes.iter().map(SensorLine::Probe))
+        .chain(core::iter::once(SensorLine::Supply(&sensors.supply)))
+}
+
+/// Split text to lines of at most `width` characters at spaces
+struct WordWrap<'a> {
+    text: &'a str,
+    width: usize,
+}
+
+impl<'a> WordWrap<'a> {
+    fn new(text: &'a str, width: usize) -> Self {
+        Self { text, width }
+    }
+}
+
+impl<'a> Iterator for WordWrap<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let text = self.text.trim_start();
+
+        if text.is_empty() {
+            return None;
+        }
+
+        if text.len() <= self.width {
+            self.text = "";
+            return Some(text);
+        }
+
+        // Words longer than the line are split anywhere
+        let split = text[..=self.width].rfind(' ').unwrap_or(self.width);
+        let (line, rest) = text.split_at(split);
+        self.text = rest;
+        Some(line)
+    }
+}
 
 pub fn print_card_size(
     debug: &mut dyn Write,
@@ -20,10 +155,33 @@ pub fn print_card_size(
     }
 }
 
+/// Devices found on the I2C bus at startup, one per line
+pub fn format_i2c_scan(
+    output: &mut dyn Write,
+    scan: &I2cScan,
+) {
+    if scan.devices().is_empty() {
+        let _ = write!(output, "No I2C devices");
+    }
+
+    for (index, device) in scan.devices().iter().enumerate() {
+        let separator = if index == 0 { "" } else { "\n" };
+        let _ = write!(output, "{}{:02X} {}", separator, device.address, device.chip);
+    }
+}
+
 pub fn format_sensors_display(
     output: &mut dyn Write,
     sensors: &Sensors,
+    page: usize,
 ) {
+    let sensor_pages = sensor_page_count(sensors);
+
+    if page >= sensor_pages {
+        format_diagnostics(output, &sensors.health, page - sensor_pages);
+        return;
+    }
+
     match sensors.time {
         Some(time) => format_date(output, time),
         None => { let _ = write!(output, "Time unknown"); },
@@ -38,34 +196,323 @@ pub fn format_sensors_display(
 
     let _ = writeln!(output);
 
-    for temperature_humidity in sensors.temperature_humidity.iter() {
-        match temperature_humidity {
-            Some(ref values) => format_temperature_humidity(output, values),
-            None => { let _ = write!(output, "TempHumi unknown"); },
-        };
+    let lines = sensor_lines(sensors)
+        .skip(page*DISPLAY_SENSOR_LINES)
+        .take(DISPLAY_SENSOR_LINES);
 
+    for line in lines {
+        format_sensor_line(output, sensors, line);
         let _ = writeln!(output);
     }
 }
 
+/// Screen of the running calibration procedure
+pub fn format_calibration(
+    output: &mut dyn Write,
+    step: CalibrationStep,
+    raw_values: &RawValues,
+    calibrations: &Calibrations,
+) {
+    match step {
+        CalibrationStep::Idle => {},
+        CalibrationStep::Select(CALIBRATION_CHANNEL_COUNT) => {
+            let _ = write!(output, "Calibration\nExit\n\nUp/Down choose\nMode exit");
+        },
+        CalibrationStep::Select(index) => {
+            let channel = CalibrationChannel::from_index(index);
+            let _ = writeln!(output, "Calibration\n{}", channel);
+
+            if let Some(raw) = raw_values.get(channel) {
+                let _ = write!(output, "Raw ");
+                let _ = write_fixed_point(output, raw, channel.decimals());
+                let _ = write!(output, "\nCal ");
+                let _ = write_fixed_point(output, calibrations.apply(index, raw), channel.decimals());
+            }
+
+            let _ = write!(output, "\nUp/Down choose\nMode calibrate");
+        },
+        CalibrationStep::Point { channel, first, reference } => {
+            let point = if first.is_some() { 2 } else { 1 };
+            let _ = write!(output, "Calibrate {}\nPoint {}/2\nRaw ", channel, point);
+
+            match raw_values.get(channel) {
+                Some(raw) => { let _ = write_fixed_point(output, raw, channel.decimals()); },
+                None => { let _ = write!(output, "unknown"); },
+            }
+
+            let _ = write!(output, "\nRef ");
+            let _ = write_fixed_point(output, reference, channel.decimals());
+            let _ = write!(output, "\nUp/Down set Ref\nMode take point");
+        },
+        CalibrationStep::Finished(channel, saved) => {
+            let result = if saved { "Saved" } else { "Failed, check\nthe points" };
+            let _ = write!(output, "Calibrate {}\n{}\n\nPress any button", channel, result);
+        },
+    }
+}
+
+/// Share of good readings, quality of the latest one and for a failing
+/// channel the number of failures in a row and the age of the last good value
+fn format_diagnostics(
+    output: &mut dyn Write,
+    health: &SensorHealth,
+    page: usize,
+) {
+    let _ = writeln!(output, "Diagnostics {}/{}", page + 1, diagnostics_page_count(health));
+
+    let channels = health.iter()
+        .skip(page*DIAGNOSTICS_LINES)
+        .take(DIAGNOSTICS_LINES);
+
+    for (channel, health) in channels {
+        let _ = write!(output, "{} ", channel);
+        format_channel_health(output, health);
+        let _ = writeln!(output);
+    }
+}
+
+fn format_channel_health(
+    output: &mut dyn Write,
+    health: &ChannelHealth,
+) {
+    match health.good_percentage() {
+        Some(percentage) => { let _ = write!(output, "{}% {}", percentage, health.quality); },
+        None => { let _ = write!(output, "no data"); },
+    }
+
+    if health.consecutive_failures > 0 {
+        let _ = write!(output, " x{}", health.consecutive_failures);
+
+        if let Some(seconds) = health.since_good {
+            let _ = write!(output, " ");
+            let _ = format_age(output, seconds);
+        }
+    }
+}
+
+/// Age in seconds, minutes or hours, whichever is the largest nonzero
+pub fn format_age(
+    output: &mut dyn Write,
+    seconds: u32,
+) -> Result<(), core::fmt::Error> {
+    match seconds {
+        0..=59 => write!(output, "{}s", seconds),
+        60..=3599 => write!(output, "{}m", seconds/60),
+        _ => write!(output, "{}h", seconds/3600),
+    }
+}
+
+fn format_sensor_line(
+    output: &mut dyn Write,
+    sensors: &Sensors,
+    line: SensorLine,
+) {
+    match line {
+        SensorLine::Ventilation(Some(ventilation)) => {
+            let _ = match ventilation.advice {
+                Advice::Ventilate => write!(output, "Ventilate"),
+                Advice::KeepClosed => write!(output, "Keep closed"),
+            };
+
+            if ventilation.fan == Some(true) {
+                let _ = write!(output, " FAN");
+            }
+        },
+        SensorLine::Ventilation(None) => { let _ = write!(output, "Ventilation unknown"); },
+        SensorLine::SeaLevelPressure(pressure, tendency) => {
+            let _ = write!(output, "QNH ");
+            let _ = write_fixed_point(output, (pressure + 5)/10, 1);
+            let _ = write!(output, " ");
+            format_pressure_tendency(output, tendency);
+        },
+        SensorLine::Forecast(forecast) => {
+            let _ = write!(output, "Forecast {}", forecast.letter());
+
+            if forecast.exceptional {
+                let _ = write!(output, "!");
+            }
+        },
+        SensorLine::Text(text) => { let _ = write!(output, "{}", text); },
+        SensorLine::Bme280Humidity(humidity) => {
+            let _ = write!(output, "Humidity {}.{} %", humidity/10, humidity % 10);
+        },
+        SensorLine::Co2(Some(values)) => {
+            let _ = write!(output, "CO2 {} ppm", values.co2);
+
+            if sensors.co2_alarm() {
+                let _ = write!(output, " ALARM");
+            }
+        },
+        SensorLine::Co2(None) => { let _ = write!(output, "CO2 unknown"); },
+        SensorLine::ParticulateMatter(Some(values)) => {
+            // PM1.0, PM2.5 and PM10 in ug/m3
+            let _ = write!(output, "PM {} {} {}", values.pm1_0, values.pm2_5, values.pm10);
+        },
+        SensorLine::ParticulateMatter(None) => { let _ = write!(output, "PM unknown"); },
+        SensorLine::Sht(reading) => format_sht_reading(output, reading),
+        SensorLine::Dht(index, values) => {
+            let _ = write!(output, "{} ", index + 1);
+
+            match values {
+                Ok(Held { value, age: None }) => format_temperature_humidity(output, value),
+                Ok(Held { value, age: Some(age) }) => format_stale_temperature_humidity(output, value, *age),
+                Err(error) => { let _ = write!(output, "TempHumi err {}", error); },
+            };
+        },
+        SensorLine::Derived(index, metric, value) => {
+            let _ = write!(output, "{} {} ", index + 1, metric.label());
+            let _ = write_fixed_point(output, value/10, 1);
+            let _ = write!(output, "{}", metric.unit());
+        },
+        SensorLine::Mold(index, risk) => {
+            let _ = write!(output, "{} Mold ", index + 1);
+            let _ = write_fixed_point(output, risk.index as i32, 2);
+            let _ = write!(output, " {}", risk.level());
+        },
+        SensorLine::Probe(probe) => format_temperature_probe(output, probe),
+        SensorLine::Supply(values) => format_supply_voltage(output, values),
+    }
+}
+
+/// Change in hPa per 3 hours followed by an arrow
+fn format_pressure_tendency(
+    output: &mut dyn Write,
+    tendency: Option<&PressureTendency>,
+) {
+    match tendency {
+        Some(tendency) => {
+            if tendency.change >= 0 {
+                let _ = write!(output, "+");
+            }
+
+            let _ = write_fixed_point(output, tendency.change/10, 1);
+
+            let arrow = match tendency.trend {
+                Trend::Rising => "^",
+                Trend::Steady => "=",
+                Trend::Falling => "v",
+            };
+
+            let _ = write!(output, " {}", arrow);
+        },
+        None => { let _ = write!(output, "?"); },
+    }
+}
+
+fn format_sht_reading(
+    output: &mut dyn Write,
+    reading: &ShtReading,
+) {
+    let _ = write!(output, "SHT ");
+
+    match reading.measurement {
+        Ok(ref values) => {
+            let _ = write_fixed_point(output, values.temperature, 2);
+            let _ = write!(output, "C ");
+            let _ = write_fixed_point(output, values.humidity as i32, 2);
+            let _ = write!(output, "%");
+        },
+        Err(error) => { let _ = write!(output, "err {}", error); },
+    }
+
+    if reading.heated {
+        let _ = write!(output, " H");
+    }
+}
+
+fn format_temperature_probe(
+    output: &mut dyn Write,
+    probe: &TemperatureProbe,
+) {
+    match probe.source {
+        ProbeSource::Ds18b20(ref address) => {
+            // Two lowest serial number bytes are enough to tell probes apart
+            let _ = write!(output, "DS{:02X}{:02X} ", address.0[2], address.0[1]);
+        },
+        ProbeSource::Thermocouple => { let _ = write!(output, "TC "); },
+        ProbeSource::Rtd => { let _ = write!(output, "RTD "); },
+        ProbeSource::Ntc(index) => { let _ = write!(output, "NTC{} ", index + 1); },
+    }
+
+    match probe.temperature {
+        Ok(temperature) => {
+            let _ = write_fixed_point(output, temperature, 2);
+            let _ = write!(output, " C");
+        },
+        Err(error) => { let _ = write!(output, "{}", error); },
+    }
+}
+
+fn format_supply_voltage(
+    output: &mut dyn Write,
+    values: &SupplyVoltage,
+) {
+    match values.battery {
+        Some(battery) => {
+            let _ = write!(output, "Bat {}% ", values.percentage);
+            let _ = write_fixed_point(output, battery as i32, 3);
+            let _ = write!(output, " V");
+        },
+        None => {
+            let _ = write!(output, "Supply ");
+            let _ = write_fixed_point(output, values.supply as i32, 3);
+            let _ = write!(output, " V");
+        },
+    }
+}
+
 fn format_temperature_humidity(
     output: &mut dyn Write,
     values: &Measurement
 ) {
-    let _ = write!(output, "{}.{} C", values.temperature/10, values.temperature%10);
+    let _ = write_fixed_point(output, values.temperature as i32, 1);
+    let _ = write!(output, " C");
     let _ = write!(output, "   ");
     let _ = write!(output, "{}.{} %", values.humidity/10, values.humidity%10);
 }
 
+/// Shorter than a fresh value to make room for the "~" marker and the age
+fn format_stale_temperature_humidity(
+    output: &mut dyn Write,
+    values: &Measurement,
+    age: u32,
+) {
+    let _ = write_fixed_point(output, values.temperature as i32, 1);
+    let _ = write!(output, " C {}.{}% ~", values.humidity/10, values.humidity%10);
+    let _ = format_age(output, age);
+}
+
 fn format_temperature_pressure(
     output: &mut dyn Write,
     values: &TemperaturePressure
 )  {
-    let _ = write!(output, "{}.{:02} C", values.temperature/100, values.temperature % 100);
+    let _ = write_fixed_point(output, values.temperature, 2);
+    let _ = write!(output, " C");
     let _ = write!(output, "  ");
     let _ = write!(output, "{}.{:02} hPa", values.pressure/100, values.pressure % 100);
 }
 
+/// Write fixed point `value` having `decimals` decimal places, the sign
+/// is kept also for values between -1 and 0
+pub fn write_fixed_point(
+    output: &mut dyn Write,
+    value: i32,
+    decimals: u32,
+) -> Result<(), core::fmt::Error> {
+    let divisor = 10_u32.pow(decimals);
+    let sign = if value < 0 { "-" } else { "" };
+    let magnitude = value.unsigned_abs();
+
+    write!(
+        output,
+        "{}{}.{:0width$}",
+        sign,
+        magnitude/divisor,
+        magnitude % divisor,
+        width = decimals as usize
+    )
+}
+
 fn format_date(
     destination: &mut dyn Write,
     datetime: DateTime
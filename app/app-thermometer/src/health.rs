@@ -0,0 +1,228 @@
+use core::fmt::{Debug, Display};
+use arrayvec::ArrayVec;
+use lib_bmx280::Bmx280Error;
+use lib_health::{ChannelMonitor, ChannelHealth, ChannelConfig, Fault};
+
+use crate::config::{
+    DHT_SENSOR_COUNT, MAX_TEMPERATURE_PROBES, MAX_SHT_SENSORS, DHT_HEALTH, BMX280_HEALTH, CO2_HEALTH,
+    PARTICULATE_HEALTH, DS18B20_HEALTH, THERMOCOUPLE_HEALTH, RTD_HEALTH, NTC_HEALTH, SHT_HEALTH,
+    SUPPLY_HEALTH
+};
+use crate::sensors::{
+    Measurement, DhtError, TemperaturePressure, Co2Measurement, ParticulateMatter, TemperatureProbe,
+    ProbeSource, ProbeError, ShtReading, ShtError, SupplyVoltage
+};
+
+/// DHT sensors, BMx280, SCD4x and PMS5003, every temperature probe and SHT
+/// sensor and the supply voltage
+pub const HEALTH_CHANNEL_COUNT: usize = DHT_SENSOR_COUNT + 3 + MAX_TEMPERATURE_PROBES + MAX_SHT_SENSORS + 1;
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Channel {
+    Dht(usize),
+    Bmx280,
+    Co2,
+    ParticulateMatter,
+    Probe(ProbeSource),
+    /// SHT sensor with the I2C address
+    Sht(u8),
+    Supply,
+}
+
+impl Display for Channel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Channel::Dht(index) => write!(f, "DHT{}", index + 1),
+            Channel::Bmx280 => write!(f, "BMX"),
+            Channel::Co2 => write!(f, "CO2"),
+            Channel::ParticulateMatter => write!(f, "PM"),
+            // Two lowest serial number bytes are enough to tell probes apart
+            Channel::Probe(ProbeSource::Ds18b20(address)) => {
+                write!(f, "DS{:02X}{:02X}", address.0[2], address.0[1])
+            },
+            Channel::Probe(ProbeSource::Thermocouple) => write!(f, "TC"),
+            Channel::Probe(ProbeSource::Rtd) => write!(f, "RTD"),
+            Channel::Probe(ProbeSource::Ntc(index)) => write!(f, "NTC{}", index + 1),
+            Channel::Sht(address) => write!(f, "SHT{:02X}", address),
+            Channel::Supply => write!(f, "VDD"),
+        }
+    }
+}
+
+/// Health of every channel after the latest reading
+#[derive(Clone)]
+pub struct SensorHealth {
+    channels: ArrayVec<(Channel, ChannelHealth), HEALTH_CHANNEL_COUNT>,
+}
+
+impl SensorHealth {
+    pub fn iter(&self) -> impl Iterator<Item = (Channel, &ChannelHealth)> {
+        self.channels.iter().map(|(channel, health)| (*channel, health))
+    }
+}
+
+/// Counters and plausibility checks of all sensors, the probes and SHT
+/// sensors get their monitor when they are seen for the first time, so the
+/// statistics stay with the sensor when another one appears or disappears
+pub struct HealthMonitor {
+    dht: [ChannelMonitor<2>; DHT_SENSOR_COUNT],
+    bmx280: ChannelMonitor<2>,
+    co2: ChannelMonitor<1>,
+    particulate_matter: ChannelMonitor<1>,
+    probes: ArrayVec<(ProbeSource, ChannelMonitor<1>), MAX_TEMPERATURE_PROBES>,
+    sht: ArrayVec<(u8, ChannelMonitor<2>), MAX_SHT_SENSORS>,
+    supply: ChannelMonitor<1>,
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self {
+            dht: core::array::from_fn(|_| ChannelMonitor::new(DHT_HEALTH)),
+            bmx280: ChannelMonitor::new(BMX280_HEALTH),
+            co2: ChannelMonitor::new(CO2_HEALTH),
+            particulate_matter: ChannelMonitor::new(PARTICULATE_HEALTH),
+            probes: ArrayVec::new(),
+            sht: ArrayVec::new(),
+            supply: ChannelMonitor::new(SUPPLY_HEALTH),
+        }
+    }
+
+    /// Count the readings taken at `time` (monotonic seconds), drivers
+    /// returning only an `Option` report a missing value
+    pub fn update(
+        &mut self,
+        temperature_humidity: &[Result<Measurement, DhtError>; DHT_SENSOR_COUNT],
+        temperature_pressure: Result<&TemperaturePressure, Fault>,
+        co2: Option<&Co2Measurement>,
+        particulate_matter: Option<&ParticulateMatter>,
+        temperature_probes: &[TemperatureProbe],
+        sht_sensors: &[ShtReading],
+        supply: &SupplyVoltage,
+        time: Option<u32>,
+    ) -> SensorHealth {
+        for (monitor, reading) in self.dht.iter_mut().zip(temperature_humidity.iter()) {
+            let values = reading.as_ref()
+                .map(|values| [values.temperature as i32, values.humidity as i32])
+                .map_err(|error| dht_fault(*error));
+            monitor.update(values, time);
+        }
+
+        let values = temperature_pressure.map(|values| [values.temperature, values.pressure]);
+        self.bmx280.update(values, time);
+        let values = co2.map(|values| [values.co2 as i32]).ok_or(Fault::Missing);
+        self.co2.update(values, time);
+        let values = particulate_matter.map(|values| [values.pm2_5 as i32]).ok_or(Fault::Missing);
+        self.particulate_matter.update(values, time);
+
+        for probe in temperature_probes.iter() {
+            let values = probe.temperature.map(|temperature| [temperature]).map_err(probe_fault);
+
+            if let Some(monitor) = find_or_add_monitor(&mut self.probes, probe.source, probe_config) {
+                monitor.update(values, time);
+            }
+        }
+
+        for reading in sht_sensors.iter() {
+            let values = reading.measurement
+                .map(|values| [values.temperature, values.humidity as i32])
+                .map_err(sht_fault);
+
+            if let Some(monitor) = find_or_add_monitor(&mut self.sht, reading.address, |_| SHT_HEALTH) {
+                monitor.update(values, time);
+            }
+        }
+
+        self.supply.update(Ok([supply.supply as i32]), time);
+
+        let mut channels = ArrayVec::new();
+
+        for (index, monitor) in self.dht.iter().enumerate() {
+            channels.push((Channel::Dht(index), monitor.health()));
+        }
+
+        channels.push((Channel::Bmx280, self.bmx280.health()));
+        channels.push((Channel::Co2, self.co2.health()));
+        channels.push((Channel::ParticulateMatter, self.particulate_matter.health()));
+
+        for (source, monitor) in self.probes.iter() {
+            channels.push((Channel::Probe(*source), monitor.health()));
+        }
+
+        for (address, monitor) in self.sht.iter() {
+            channels.push((Channel::Sht(*address), monitor.health()));
+        }
+
+        channels.push((Channel::Supply, self.supply.health()));
+
+        SensorHealth { channels }
+    }
+}
+
+/// Monitor of the sensor with `key`, a new one is added for a sensor
+/// seen for the first time while there is space left
+fn find_or_add_monitor<K, const Q: usize, const N: usize>(
+    monitors: &mut ArrayVec<(K, ChannelMonitor<Q>), N>,
+    key: K,
+    config: impl Fn(K) -> ChannelConfig<Q>,
+) -> Option<&mut ChannelMonitor<Q>>
+where K: PartialEq + Copy {
+    let position = match monitors.iter().position(|(known, _)| *known == key) {
+        Some(position) => position,
+        None => {
+            monitors.try_push((key, ChannelMonitor::new(config(key)))).ok()?;
+            monitors.len() - 1
+        },
+    };
+
+    Some(&mut monitors[position].1)
+}
+
+fn probe_config(source: ProbeSource) -> ChannelConfig<1> {
+    match source {
+        ProbeSource::Ds18b20(_) => DS18B20_HEALTH,
+        ProbeSource::Thermocouple => THERMOCOUPLE_HEALTH,
+        ProbeSource::Rtd => RTD_HEALTH,
+        ProbeSource::Ntc(_) => NTC_HEALTH,
+    }
+}
+
+fn probe_fault(error: ProbeError) -> Fault {
+    match error {
+        ProbeError::Bus => Fault::Bus,
+        ProbeError::NotResponding => Fault::NoResponse,
+        ProbeError::CrcMismatch => Fault::CrcMismatch,
+        ProbeError::NotConverted => Fault::Missing,
+        // Broken wiring of the probe, the converter answers without a value
+        ProbeError::OpenCircuit
+        | ProbeError::ShortCircuit
+        | ProbeError::ShortToGnd
+        | ProbeError::ShortToVcc
+        | ProbeError::OverUnderVoltage => Fault::Wiring,
+    }
+}
+
+fn sht_fault(error: ShtError) -> Fault {
+    match error {
+        ShtError::NoResponse => Fault::NoResponse,
+        ShtError::Bus => Fault::Bus,
+        ShtError::CrcMismatch => Fault::CrcMismatch,
+    }
+}
+
+fn dht_fault(error: DhtError) -> Fault {
+    match error {
+        DhtError::NoResponse => Fault::NoResponse,
+        DhtError::Timeout => Fault::Timeout,
+        DhtError::CrcMismatch => Fault::CrcMismatch,
+        DhtError::Gpio => Fault::Bus,
+    }
+}
+
+pub fn bmx280_fault<E>(error: &Bmx280Error<E>) -> Fault
+where E: Debug {
+    match error {
+        Bmx280Error::Bus(_) | Bmx280Error::UnknownChip(_) => Fault::Bus,
+        Bmx280Error::NotReady => Fault::Timeout,
+        Bmx280Error::Reset => Fault::Missing,
+    }
+}
\ No newline at end of file
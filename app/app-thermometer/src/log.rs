@@ -1,21 +1,89 @@
 use core::fmt::Write;
 use arrayvec::ArrayString;
-use dht11::Measurement;
 use pcf8563::DateTime;
+use lib_weather::{PressureTendency, Trend, Forecast};
 
-use crate::sensors::{Sensors, TemperaturePressure};
+use crate::config::{
+    DHT_SENSOR_COUNT, MAX_TEMPERATURE_PROBES, MAX_SHT_SENSORS, DHT_LOG_METRICS, DHT_CHANNELS,
+    LOG_RAW_VALUES
+};
+use crate::format::write_fixed_point;
+use crate::ventilation::{Ventilation, Advice};
+use crate::mold::MoldSummary;
+use crate::health::HEALTH_CHANNEL_COUNT;
+use crate::calibration::CALIBRATION_CHANNEL_COUNT;
+use lib_health::Held;
+use crate::sensors::{
+    Sensors, TemperaturePressure, Measurement, ProbeSource, ShtMeasurement, Co2Measurement,
+    ParticulateMatter, I2cScan, MAX_I2C_DEVICES
+};
+
+/// Capacity of a single log record, which contains
+/// - date, time, BMP280/BME280 values, sea level pressure, tendency and
+///   forecast, CO2, particulate matter, supply, MCU temperature, I2C
+///   recoveries and ventilation advice
+/// - serial number and values of every SHT sensor
+/// - temperature, humidity, derived values and mold index of every DHT sensor
+/// - identification and temperature of every probe
+/// - quality flags of every monitored channel
+/// - raw value of every calibrated channel, if enabled
+///
+/// Every part is sized for its longest output and rounded up, the header
+/// preceding the first record of a file needs 12 bytes per I2C device
+pub const LOG_RECORD_CAPACITY: usize =
+    272 + 32*MAX_SHT_SENSORS + 68*DHT_SENSOR_COUNT + 28*MAX_TEMPERATURE_PROBES
+    + 20*HEALTH_CHANNEL_COUNT + 20*CALIBRATION_CHANNEL_COUNT + 16 + 12*MAX_I2C_DEVICES;
 
 pub fn format_file_name(
     sensors: &Sensors,
 ) -> Option<ArrayString<15>> {
-    sensors.time.as_ref().and_then(|time| {
-        let mut buffer = ArrayString::<15>::new();
+    sensors.time.as_ref().and_then(format_date_file_name)
+}
 
-        match format_log_file_name(&mut buffer, time) {
-            Ok(_) => Some(buffer),
-            Err(_) => None,
+/// Name of the log file of the given day
+pub fn format_date_file_name(
+    date: &DateTime,
+) -> Option<ArrayString<15>> {
+    let mut buffer = ArrayString::<15>::new();
+
+    match format_log_file_name(&mut buffer, date) {
+        Ok(_) => Some(buffer),
+        Err(_) => None,
+    }
+}
+
+/// Daily figure of the mold index of every tracked room, written at the
+/// end of the log of the finished day
+pub fn format_mold_summary(
+    output: &mut dyn Write,
+    summary: &MoldSummary,
+) {
+    let _ = format_date(output, &summary.date);
+    let _ = write!(output, " MoldDaily ");
+
+    for (room, maximum) in summary.daily_maximum.iter().enumerate() {
+        if DHT_CHANNELS[room].mold {
+            let _ = write!(output, "{}=", room + 1);
+            print_optional(output, maximum.map(|value| value as i32).as_ref(), format_mold_index);
         }
-    })
+    }
+
+    let _ = write!(output, "End\n");
+}
+
+/// Devices found on the I2C bus at startup, written before the first
+/// record of every file after a restart
+pub fn format_log_header(
+    output: &mut dyn Write,
+    scan: &I2cScan,
+) {
+    let _ = write!(output, "I2C ");
+
+    for device in scan.devices() {
+        let _ = write!(output, "{:02X}={} ", device.address, device.chip);
+    }
+
+    let _ = write!(output, "End\n");
 }
 
 pub fn format_sensors_log(
@@ -26,10 +94,90 @@ pub fn format_sensors_log(
     print_optional(output, sensors.time.as_ref(), format_time);
     print_optional(output, sensors.temperature_pressure.as_ref(), format_bmp280_temperature);
     print_optional(output, sensors.temperature_pressure.as_ref(), format_bmp280_pressure);
+    print_optional(
+        output,
+        sensors.temperature_pressure.as_ref().and_then(|values| values.humidity.as_ref()),
+        format_bme280_humidity
+    );
+
+    let _ = write!(output, "QNH=");
+    print_optional(output, sensors.sea_level_pressure.as_ref(), format_pressure);
+    let _ = write!(output, "Tend=");
+    print_optional(output, sensors.pressure_tendency.as_ref(), format_pressure_tendency);
+    let _ = write!(output, "Fcst=");
+    print_optional(output, sensors.forecast.as_ref(), format_forecast);
 
-    for temperature_humidity in sensors.temperature_humidity.iter() {
-        print_optional(output, temperature_humidity.as_ref(), format_dht11_temperature);
-        print_optional(output, temperature_humidity.as_ref(), format_dht11_humidity);
+    let _ = write!(output, "CO2=");
+    print_optional(output, sensors.co2.as_ref(), format_co2);
+
+    if sensors.co2_alarm() {
+        let _ = write!(output, "CO2Alarm ");
+    }
+
+    let _ = write!(output, "PM=");
+    print_optional(output, sensors.particulate_matter.as_ref(), format_particulate_matter);
+
+    let _ = write!(output, "Supply=");
+    print_optional(output, Some(&sensors.supply.supply), format_millivolts);
+    let _ = write!(output, "Bat=");
+    print_optional(output, sensors.supply.battery.as_ref(), format_millivolts);
+
+    if sensors.supply.low {
+        let _ = write!(output, "LowBattery ");
+    }
+
+    let _ = write!(output, "MCU=");
+    print_optional(output, Some(&sensors.mcu_temperature), format_probe_temperature);
+    let _ = write!(output, "I2CRec={} ", sensors.i2c_recoveries);
+    let _ = write!(output, "Vent=");
+    print_optional(output, sensors.ventilation.as_ref(), format_ventilation);
+
+    for reading in sensors.sht_sensors.iter() {
+        let _ = format_sht_serial(output, reading.serial);
+        print_result(output, reading.measurement.as_ref(), format_sht_temperature);
+        print_result(output, reading.measurement.as_ref(), format_sht_humidity);
+
+        if reading.heated {
+            let _ = write!(output, "Heat ");
+        }
+    }
+
+    for (index, temperature_humidity) in sensors.temperature_humidity.iter().enumerate() {
+        print_result(output, temperature_humidity.as_ref(), format_dht_temperature);
+        print_result(output, temperature_humidity.as_ref(), format_dht_humidity);
+
+        if let Ok(Held { age: Some(age), .. }) = temperature_humidity {
+            let _ = write!(output, "Stale={} ", age);
+        }
+
+        for metric in DHT_LOG_METRICS[index].iter() {
+            let value = sensors.temperature_humidity_derived[index].map(|values| metric.value(&values));
+            let _ = write!(output, "{}=", metric.label());
+            print_optional(output, value.as_ref(), format_derived_value);
+        }
+
+        if let Some(risk) = sensors.mold[index] {
+            let _ = write!(output, "Mold=");
+            let _ = format_mold_index(output, &(risk.index as i32));
+            let _ = write!(output, " ");
+        }
+    }
+
+    for probe in sensors.temperature_probes.iter() {
+        let _ = format_probe_source(output, &probe.source);
+        print_result(output, probe.temperature.as_ref(), format_probe_temperature);
+    }
+
+    for (channel, health) in sensors.health.iter() {
+        let _ = write!(output, "{}Q={} ", channel, health.quality);
+    }
+
+    if LOG_RAW_VALUES {
+        for (channel, value) in sensors.raw.iter() {
+            let _ = write!(output, "Raw{}=", channel);
+            let _ = write_fixed_point(output, value, channel.decimals());
+            let _ = write!(output, " ");
+        }
     }
 
     let _ = write!(output, "End\n");
@@ -51,6 +199,23 @@ fn print_optional<T, F>(
     }
 }
 
+/// Like `print_optional`, but the reason of a missing value is logged
+fn print_result<T, E, F>(
+    output: &mut dyn Write,
+    value: Result<&T, &E>,
+    formatter: F
+) where F: Fn(&mut dyn Write, &T) -> Result<(), core::fmt::Error>, E: core::fmt::Display {
+    match value {
+        Ok(value) => {
+            let _ = formatter(output, value);
+            let _ = write!(output, " ");
+        },
+        Err(error) => {
+            let _ = write!(output, "?{} ", error);
+        },
+    }
+}
+
 fn format_log_file_name(
     output: &mut dyn Write,
     value: &DateTime,
@@ -76,7 +241,7 @@ fn format_bmp280_temperature(
     output: &mut dyn Write,
     value: &TemperaturePressure,
 ) -> Result<(), core::fmt::Error> {
-    write!(output, "{}.{:02}", value.temperature/100, value.temperature % 100)
+    write_fixed_point(output, value.temperature, 2)
 }
 
 fn format_bmp280_pressure(
@@ -86,16 +251,171 @@ fn format_bmp280_pressure(
     write!(output, "{}.{:02}", value.pressure/100, value.pressure % 100)
 }
 
-fn format_dht11_temperature(
+/// Pressure in hPa with pascal resolution
+fn format_pressure(
+    output: &mut dyn Write,
+    value: &i32,
+) -> Result<(), core::fmt::Error> {
+    write_fixed_point(output, *value, 2)
+}
+
+/// Change in hPa per 3 hours followed by the trend
+fn format_pressure_tendency(
+    output: &mut dyn Write,
+    value: &PressureTendency,
+) -> Result<(), core::fmt::Error> {
+    if value.change >= 0 {
+        write!(output, "+")?;
+    }
+
+    write_fixed_point(output, value.change, 2)?;
+
+    match value.trend {
+        Trend::Rising => write!(output, " Rising"),
+        Trend::Steady => write!(output, " Steady"),
+        Trend::Falling => write!(output, " Falling"),
+    }
+}
+
+/// Zambretti letter, marked when the pressure is out of the forecast range
+fn format_forecast(
+    output: &mut dyn Write,
+    value: &Forecast,
+) -> Result<(), core::fmt::Error> {
+    write!(output, "{}", value.letter())?;
+
+    match value.exceptional {
+        true => write!(output, "!"),
+        false => Ok(()),
+    }
+}
+
+fn format_bme280_humidity(
+    output: &mut dyn Write,
+    value: &u16,
+) -> Result<(), core::fmt::Error> {
+    write!(output, "{}.{}", value/10, value % 10)
+}
+
+fn format_co2(
+    output: &mut dyn Write,
+    value: &Co2Measurement,
+) -> Result<(), core::fmt::Error> {
+    write!(output, "{} ", value.co2)?;
+    write_fixed_point(output, value.temperature, 2)?;
+    write!(output, " ")?;
+    write_fixed_point(output, value.humidity as i32, 2)
+}
+
+/// PM1.0, PM2.5 and PM10 under atmospheric environment in ug/m3
+fn format_particulate_matter(
+    output: &mut dyn Write,
+    value: &ParticulateMatter,
+) -> Result<(), core::fmt::Error> {
+    write!(output, "{} {} {}", value.pm1_0, value.pm2_5, value.pm10)
+}
+
+/// Voltage in volts with millivolt resolution
+fn format_millivolts(
     output: &mut dyn Write,
-    value: &Measurement,
+    value: &u16,
 ) -> Result<(), core::fmt::Error> {
-    write!(output, "{}.{:02}", value.temperature/10, value.temperature % 10)
+    write_fixed_point(output, *value as i32, 3)
 }
 
-fn format_dht11_humidity(
+/// Serial number is logged with every record for traceability
+fn format_sht_serial(
     output: &mut dyn Write,
-    value: &Measurement,
+    value: Option<u32>,
 ) -> Result<(), core::fmt::Error> {
-    write!(output, "{}.{}", value.humidity/10, value.humidity%10)
-}
\ No newline at end of file
+    match value {
+        Some(serial) => write!(output, "SHT{:08X}=", serial),
+        None => write!(output, "SHT?="),
+    }
+}
+
+fn format_sht_temperature(
+    output: &mut dyn Write,
+    value: &ShtMeasurement,
+) -> Result<(), core::fmt::Error> {
+    write_fixed_point(output, value.temperature, 2)
+}
+
+fn format_sht_humidity(
+    output: &mut dyn Write,
+    value: &ShtMeasurement,
+) -> Result<(), core::fmt::Error> {
+    write_fixed_point(output, value.humidity as i32, 2)
+}
+
+fn format_dht_temperature(
+    output: &mut dyn Write,
+    value: &Held<Measurement>,
+) -> Result<(), core::fmt::Error> {
+    write_fixed_point(output, value.value.temperature as i32, 1)
+}
+
+fn format_dht_humidity(
+    output: &mut dyn Write,
+    value: &Held<Measurement>,
+) -> Result<(), core::fmt::Error> {
+    write!(output, "{}.{}", value.value.humidity/10, value.value.humidity%10)
+}
+
+fn format_derived_value(
+    output: &mut dyn Write,
+    value: &i32,
+) -> Result<(), core::fmt::Error> {
+    write_fixed_point(output, *value, 2)
+}
+
+/// Advice with the values it is based on, so that every decision
+/// can be traced back
+fn format_ventilation(
+    output: &mut dyn Write,
+    value: &Ventilation,
+) -> Result<(), core::fmt::Error> {
+    match value.advice {
+        Advice::Ventilate => write!(output, "Open")?,
+        Advice::KeepClosed => write!(output, "Closed")?,
+    }
+
+    write!(output, " dAH=")?;
+    write_fixed_point(output, value.humidity_difference, 2)?;
+    write!(output, " dDP=")?;
+    write_fixed_point(output, value.dew_point_difference, 2)?;
+
+    match value.fan {
+        Some(true) => write!(output, " Fan=On"),
+        Some(false) => write!(output, " Fan=Off"),
+        None => Ok(()),
+    }
+}
+
+fn format_mold_index(
+    output: &mut dyn Write,
+    value: &i32,
+) -> Result<(), core::fmt::Error> {
+    write_fixed_point(output, *value, 2)
+}
+
+/// Probes are discovered at runtime, so every value is prefixed
+/// with the probe identification
+fn format_probe_source(
+    output: &mut dyn Write,
+    value: &ProbeSource,
+) -> Result<(), core::fmt::Error> {
+    match value {
+        ProbeSource::Ds18b20(address) => write!(output, "{}=", address),
+        ProbeSource::Thermocouple => write!(output, "TC="),
+        ProbeSource::Rtd => write!(output, "RTD="),
+        ProbeSource::Ntc(index) => write!(output, "NTC{}=", index + 1),
+    }
+}
+
+fn format_probe_temperature(
+    output: &mut dyn Write,
+    value: &i32,
+) -> Result<(), core::fmt::Error> {
+    write_fixed_point(output, *value, 2)
+}
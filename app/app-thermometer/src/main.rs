@@ -2,27 +2,72 @@
 #![no_main]
 
 mod panic;
+mod config;
 mod format;
 mod sensors;
 mod log;
 mod display;
+mod metrics;
+mod ventilation;
+mod backup;
+mod mold;
+mod health;
+mod calibration;
+mod buttons;
+mod storage;
+mod filter;
+mod power;
 
-use core::{cell::Cell, fmt::Write};
+use core::{cell::RefCell, fmt::Write};
 use arrayvec::ArrayString;
 use cortex_m_rt::{entry};
 use cortex_m::peripheral::Peripherals as CortexPeripherals;
-use dht11::Dht11;
-use display::render_display;
+use display::{render_display, render_message, render_calibration};
 use embedded_hal::spi;
 use embedded_sdmmc::{Controller, SdMmcSpi, TimeSource, Timestamp};
-use log::{format_file_name, format_sensors_log};
+use log::{
+    format_file_name, format_date_file_name, format_sensors_log, format_mold_summary,
+    format_log_header, LOG_RECORD_CAPACITY
+};
 use panic::halt_with_error_led;
+use power::enter_standby;
 use hx1230::{ArrayDisplayBuffer, SpiDriver};
+use pcf8563::PCF8563;
 use lib_datalogger::{detect_sd_card_size, append_to_file};
-use sensors::{read_sensors, Time, Dht11Drivers};
-use stm32f4xx_hal::{prelude::*, pac::{self, Peripherals}, gpio::NoPin, i2c::I2c};
+use lib_weather::PressureHistory;
+use lib_health::Hold;
+use ventilation::VentilationAdvisor;
+use backup::BackupRegisters;
+use mold::MoldTracker;
+use health::HealthMonitor;
+use calibration::CalibrationProcedure;
+use buttons::Buttons;
+use storage::CalibrationStorage;
+use filter::SensorFilters;
+use sensors::{
+    read_sensors, Time, Dht, DhtDrivers, OneWirePin, Ds18b20Probes, ShtSensors, SpiProbes,
+    Max31855, Max31865, AnalogProbes, Scd4x, Pms5003, SupplyMonitor,
+    Bmx280Sensor, I2cScan, RecoverableI2c, I2cProxy
+};
+use stm32f4xx_hal::{
+    prelude::*, pac::{self, Peripherals}, gpio::NoPin, adc::{Adc, config::AdcConfig},
+    serial::config::Config as SerialConfig
+};
 
-use crate::format::print_card_size;
+use crate::config::{
+    DHT_CHANNELS, DHT_SENSOR_COUNT, DHT_STALE_SECONDS, DS18B20_RESOLUTION, SHT_SENSORS, RTD_CONFIG,
+    NTC_PROBES, NTC_OVERSAMPLING, SCD4X_AUTOMATIC_SELF_CALIBRATION, PMS5003_CONFIG, SUPPLY_CONFIG,
+    SUPPLY_OVERSAMPLING, BMX280_ADDRESS, BMX280_PROFILE, PRESSURE_HISTORY_INTERVAL_MINUTES,
+    VENTILATION, VENTILATION_FAN_RELAY, LOG_FILTERED_VALUES
+};
+use crate::format::{print_card_size, format_i2c_scan};
+
+/// Main loop waits 400 ms polling the buttons
+const BUTTON_POLL_MS: u32 = 20;
+const BUTTON_POLLS: u32 = 400/BUTTON_POLL_MS;
+
+/// Startup screen with the SD card and the I2C devices is shown this long
+const STARTUP_SCREEN_MS: u16 = 3000;
 
 #[entry]
 fn main() -> ! {
@@ -45,6 +90,7 @@ fn run(
 
     let gpioa = dp.GPIOA.split();
     let gpiob = dp.GPIOB.split();
+    let gpioc = dp.GPIOC.split();
 
     let mut display_cs = gpiob.pb14.into_push_pull_output();
 
@@ -62,7 +108,15 @@ fn run(
         &clocks,
     );
 
-    let i2c = I2c::new(
+    // PB3 and PB4 are JTAG pins after reset, only SWD is used for debugging
+    let probe_spi = dp.SPI3.spi(
+        (unsafe { gpiob.pb3.activate() }, unsafe { gpiob.pb4.activate() }, gpiob.pb5),
+        spi::MODE_1,
+        1000.kHz(),
+        &clocks,
+    );
+
+    let i2c_bus = RefCell::new(RecoverableI2c::new(
         dp.I2C1,
         (
             gpiob.pb8.into_alternate().set_open_drain(),
@@ -70,11 +124,23 @@ fn run(
         ),
         400.kHz(),
         &clocks,
-    );
+    ));
 
-    let i2c_container = Cell::new(Some(i2c));
-    let sd_cs = gpiob.pb0.into_push_pull_output();
+    // Every I2C driver gets its own handle to the shared bus
     let mut delay = dp.TIM5.delay_us(&clocks);
+    let i2c_scan = I2cScan::scan(&mut I2cProxy::new(&i2c_bus), &mut delay);
+
+    // Configured sensors are used when the scan finds none
+    let sht_config = i2c_scan.sht_sensors();
+    let sht_config = if sht_config.is_empty() { SHT_SENSORS } else { &sht_config[..] };
+    let mut sht_sensors = ShtSensors::new(I2cProxy::new(&i2c_bus), &mut delay, sht_config);
+    let bmx280_address = i2c_scan.bmx280_address().unwrap_or(BMX280_ADDRESS);
+    let mut bmx280 = Bmx280Sensor::new(I2cProxy::new(&i2c_bus), bmx280_address, BMX280_PROFILE);
+    let mut pressure_history = PressureHistory::new(PRESSURE_HISTORY_INTERVAL_MINUTES);
+    let mut co2_sensor = Scd4x::new(I2cProxy::new(&i2c_bus), SCD4X_AUTOMATIC_SELF_CALIBRATION);
+    let mut time_driver = PCF8563::new(I2cProxy::new(&i2c_bus));
+
+    let sd_cs = gpiob.pb0.into_push_pull_output();
     let mut frame_buffer: ArrayDisplayBuffer = ArrayDisplayBuffer::new();
 
     let mut display = SpiDriver::new(&mut display_spi, &mut display_cs);
@@ -82,17 +148,92 @@ fn run(
     let mut sd_controller = Controller::new(SdMmcSpi::new(sd_spi, sd_cs), Clock);
     let card_size = detect_sd_card_size(&mut sd_controller);
 
-    let mut thermo_drivers = Dht11Drivers::new(
-        Dht11::new(gpiob.pb10.into_open_drain_output()),
-        Dht11::new(gpioa.pa8.into_open_drain_output()),
-        Dht11::new(gpioa.pa9.into_open_drain_output()),
-        Dht11::new(gpioa.pa10.into_open_drain_output()),
-        Dht11::new(gpioa.pa11.into_open_drain_output()),
-        Dht11::new(gpioa.pa12.into_open_drain_output()),
+    // Data pins in the order of `DHT_CHANNELS`, the only place to change
+    // when sensors are added or removed besides the table itself
+    let dht_pins: [_; DHT_SENSOR_COUNT] = [
+        gpiob.pb10.into_open_drain_output().erase(),
+        gpioa.pa8.into_open_drain_output().erase(),
+        gpioa.pa9.into_open_drain_output().erase(),
+        gpioa.pa10.into_open_drain_output().erase(),
+        gpioa.pa11.into_open_drain_output().erase(),
+        gpioa.pa12.into_open_drain_output().erase(),
+    ];
+    let mut dht_pins = dht_pins.into_iter();
+
+    let mut thermo_drivers = DhtDrivers::new(
+        DHT_CHANNELS.map(|channel| Dht::new(dht_pins.next().unwrap(), channel.kind)),
+        clocks.hclk().to_MHz(),
+    );
+
+    let mut dht_holds = core::array::from_fn(|_| Hold::new(DHT_STALE_SECONDS));
+
+    let mut ds18b20_probes = Ds18b20Probes::new(
+        OneWirePin::new(gpiob.pb12.into_open_drain_output(), clocks.hclk().to_MHz()),
+        DS18B20_RESOLUTION,
+    );
+
+    let mut spi_probes = SpiProbes::new(
+        probe_spi,
+        Max31855::new(gpiob.pb6.into_push_pull_output()),
+        Max31865::new(gpiob.pb7.into_push_pull_output(), RTD_CONFIG),
+    );
+
+    let ntc0 = gpioa.pa1.into_analog();
+    let ntc1 = gpioa.pa4.into_analog();
+
+    let adc = RefCell::new(Adc::adc1(dp.ADC1, true, AdcConfig::default()));
+
+    let mut analog_probes = AnalogProbes::new(
+        &adc,
+        [&ntc0, &ntc1],
+        NTC_PROBES,
+        NTC_OVERSAMPLING,
+    );
+
+    let battery = gpiob.pb1.into_analog();
+    let mut supply_monitor = SupplyMonitor::new(&adc, &battery, SUPPLY_CONFIG, SUPPLY_OVERSAMPLING);
+
+    let pms5003_serial = dp.USART2.serial(
+        (gpioa.pa2.into_alternate(), gpioa.pa3.into_alternate()),
+        SerialConfig::default().baudrate(9600.bps()),
+        &clocks,
+    ).map_err(|_| ())?;
+
+    // PA15 is a JTAG pin after reset, only SWD is used for debugging
+    let pms5003_sleep = unsafe { gpioa.pa15.activate() }.into_push_pull_output();
+    let mut pms5003 = Pms5003::new(pms5003_serial, pms5003_sleep, PMS5003_CONFIG);
+
+    let fan_relay = gpiob.pb2.into_push_pull_output().erase();
+    let fan_relay = if VENTILATION_FAN_RELAY { Some(fan_relay) } else { None };
+    let mut ventilation = VENTILATION.map(|config| VentilationAdvisor::new(config, fan_relay));
+
+    let mut mold_tracker = MoldTracker::new(BackupRegisters::new(dp.RTC));
+    let mut health_monitor = HealthMonitor::new();
+
+    let mut calibration_storage = CalibrationStorage::new(dp.FLASH);
+    let mut calibrations = calibration_storage.load();
+    let mut calibration = CalibrationProcedure::new();
+    let mut filters = SensorFilters::new();
+
+    // PA0 is the user button of the common STM32F411 boards
+    let mut buttons = Buttons::new(
+        gpioa.pa0.into_pull_up_input().erase(),
+        gpioc.pc14.into_pull_up_input().erase(),
+        gpioc.pc15.into_pull_up_input().erase(),
     );
 
     let mut sd_result = ArrayString::<40>::new();
     print_card_size(&mut sd_result, card_size);
+
+    let mut startup = ArrayString::<160>::new();
+    let _ = writeln!(&mut startup, "{}", sd_result);
+    format_i2c_scan(&mut startup, &i2c_scan);
+    display.initialize(&mut delay).map_err(|_| ())?;
+    render_message(&mut frame_buffer, &mut display, &startup);
+    delay.delay_ms(STARTUP_SCREEN_MS);
+
+    // Every file gets the header before its first record after a restart
+    let mut header_file = None;
     let mut last_write_attempt = Time::default();
     let mut counter: u64 = 0;
 
@@ -101,29 +242,74 @@ fn run(
             display.initialize(&mut delay).map_err(|_| ())?;
         }
 
-        let i2c_local = Cell::new(None);
-        i2c_container.swap(&i2c_local);
-
-        let (sensors, i2c_returned) = read_sensors(
-            i2c_local.into_inner().unwrap(),
+        let sensors = read_sensors(
+            &i2c_bus,
+            &mut time_driver,
+            &mut bmx280,
+            &mut pressure_history,
             &mut thermo_drivers,
+            &mut dht_holds,
+            &mut [&mut ds18b20_probes, &mut spi_probes, &mut analog_probes],
+            &mut sht_sensors,
+            &mut co2_sensor,
+            &mut pms5003,
+            &mut supply_monitor,
+            ventilation.as_mut(),
+            &mut mold_tracker,
+            &mut health_monitor,
+            &calibrations,
             &mut delay
         );
 
-        let i2c_returned_cell = Cell::new(Some(i2c_returned));
-        i2c_returned_cell.swap(&i2c_container);
+        let filtered = filters.apply(&sensors);
+        let logged = if LOG_FILTERED_VALUES { &filtered } else { &sensors };
+
+        if let Some(ref summary) = sensors.mold_summary {
+            if let Some(file_name) = format_date_file_name(&summary.date) {
+                let mut file_data = ArrayString::<LOG_RECORD_CAPACITY>::new();
+                format_mold_summary(&mut file_data, summary);
+                let _ = append_to_file(&mut sd_controller, &file_name, &file_data);
+            }
+        }
+
+        if sensors.supply.low {
+            // Final record is written regardless of the logging interval,
+            // every append closes the file, so nothing is left unwritten
+            if let Some(file_name) = format_file_name(&sensors) {
+                let mut file_data = ArrayString::<LOG_RECORD_CAPACITY>::new();
+                format_sensors_log(&mut file_data, logged);
+                let _ = append_to_file(&mut sd_controller, &file_name, &file_data);
+            }
+
+            pms5003.sleep();
+            render_message(&mut frame_buffer, &mut display, "Low battery\nShut down");
+            enter_standby();
+        }
 
-        render_display(&mut frame_buffer, &mut display, &sd_result, &sensors);
+        let page = (counter/5) as usize;
+
+        if calibration.is_active() {
+            let step = calibration.step();
+            render_calibration(&mut frame_buffer, &mut display, step, &sensors, &calibrations);
+        } else {
+            render_display(&mut frame_buffer, &mut display, &sd_result, &filtered, page);
+        }
 
         if let Some(time) = sensors.get_time() {
             if time.seconds % 10 == 0 && time != last_write_attempt {
                 if let Some(file_name) = format_file_name(&sensors) {
                     last_write_attempt = time;
-                    let mut file_data = ArrayString::<200>::new();
-                    format_sensors_log(&mut file_data, &sensors);
+                    let mut file_data = ArrayString::<LOG_RECORD_CAPACITY>::new();
+
+                    if header_file != Some(file_name) {
+                        format_log_header(&mut file_data, &i2c_scan);
+                    }
+
+                    format_sensors_log(&mut file_data, logged);
                     sd_result.clear();
                     match append_to_file(&mut sd_controller, &file_name, &file_data) {
                         Ok(_) => {
+                            header_file = Some(file_name);
                             let _ = write!(&mut sd_result, "OK: {}\nWritten: {}", &file_name, time);
                         },
                         Err(error) => {
@@ -134,7 +320,24 @@ fn run(
             }
         }
 
-        delay.delay_ms(400_u16);
+        // Buttons are polled while waiting, the calibration screen follows them
+        for _ in 0..BUTTON_POLLS {
+            delay.delay_ms(BUTTON_POLL_MS as u16);
+
+            if let Some(button) = buttons.poll(BUTTON_POLL_MS) {
+                if calibration.press(button, &sensors.raw, &mut calibrations) {
+                    let _ = calibration_storage.store(&calibrations);
+                }
+
+                if calibration.is_active() {
+                    let step = calibration.step();
+                    render_calibration(&mut frame_buffer, &mut display, step, &sensors, &calibrations);
+                } else {
+                    render_display(&mut frame_buffer, &mut display, &sd_result, &filtered, page);
+                }
+            }
+        }
+
         counter += 1;
     }
 }
@@ -0,0 +1,66 @@
+use lib_weather::Psychrometrics;
+
+/// Value derived from the temperature and humidity of a sensor
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Metric {
+    DewPoint,
+    AbsoluteHumidity,
+    HeatIndex,
+    Humidex,
+}
+
+impl Metric {
+    const ALL: [Metric; 4] = [
+        Metric::DewPoint,
+        Metric::AbsoluteHumidity,
+        Metric::HeatIndex,
+        Metric::Humidex,
+    ];
+
+    /// Value in 1/100 of the metric unit
+    pub fn value(self, values: &Psychrometrics) -> i32 {
+        match self {
+            Metric::DewPoint => values.dew_point,
+            Metric::AbsoluteHumidity => values.absolute_humidity as i32,
+            Metric::HeatIndex => values.heat_index,
+            Metric::Humidex => values.humidex,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::DewPoint => "DP",
+            Metric::AbsoluteHumidity => "AH",
+            Metric::HeatIndex => "HI",
+            Metric::Humidex => "HX",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            Metric::DewPoint | Metric::HeatIndex => " C",
+            Metric::AbsoluteHumidity => " g/m3",
+            Metric::Humidex => "",
+        }
+    }
+}
+
+/// Set of metrics selected for a single sensor
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Metrics(u8);
+
+impl Metrics {
+    pub const NONE: Self = Self(0);
+
+    pub const fn with(self, metric: Metric) -> Self {
+        Self(self.0 | 1 << metric as u8)
+    }
+
+    pub fn contains(self, metric: Metric) -> bool {
+        self.0 & 1 << metric as u8 != 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Metric> {
+        Metric::ALL.into_iter().filter(move |&metric| self.contains(metric))
+    }
+}
\ No newline at end of file
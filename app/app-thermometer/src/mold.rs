@@ -0,0 +1,124 @@
+use lib_weather::MoldIndex;
+use pcf8563::DateTime;
+
+use crate::backup::{BackupRegisters, MOLD_INDEX, BACKUP_REGISTER_COUNT};
+use crate::config::{DHT_SENSOR_COUNT, DHT_CHANNELS};
+use crate::sensors::{Measurement, DhtError};
+
+// Every DHT sensor has a backup register, tracked room or not
+const _: () = assert!(
+    MOLD_INDEX + DHT_SENSOR_COUNT <= BACKUP_REGISTER_COUNT,
+    "mold index of every DHT sensor needs a backup register"
+);
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct MoldRisk {
+    /// Mold index in 1/100
+    pub index: u32,
+}
+
+impl MoldRisk {
+    /// Short description of the index, visible mold starts at 3
+    pub fn level(&self) -> &'static str {
+        match self.index {
+            0..=99 => "none",
+            100..=199 => "low",
+            200..=299 => "medium",
+            _ => "high",
+        }
+    }
+}
+
+/// Highest mold index of every room during a finished day
+#[derive(Copy, Clone)]
+pub struct MoldSummary {
+    pub date: DateTime,
+    pub daily_maximum: [Option<u32>; DHT_SENSOR_COUNT],
+}
+
+/// Mold index of the rooms measured by DHT sensors, the state is kept
+/// in backup registers to survive resets
+pub struct MoldTracker {
+    backup: BackupRegisters,
+    indexes: [MoldIndex; DHT_SENSOR_COUNT],
+    daily_maximum: [Option<u32>; DHT_SENSOR_COUNT],
+    date: Option<DateTime>,
+    last_time: Option<u32>,
+}
+
+impl MoldTracker {
+    pub fn new(backup: BackupRegisters) -> Self {
+        let indexes = core::array::from_fn(|room| {
+            MoldIndex::from_raw(backup.read(MOLD_INDEX + room)).unwrap_or_default()
+        });
+
+        Self {
+            backup,
+            indexes,
+            daily_maximum: [None; DHT_SENSOR_COUNT],
+            date: None,
+            last_time: None,
+        }
+    }
+
+    /// Integrate the measurements since the last update, `time` is a monotonic
+    /// minute counter, the summary of the previous day is returned with the
+    /// first update of a new day
+    pub fn update(
+        &mut self,
+        measurements: &[Result<Measurement, DhtError>; DHT_SENSOR_COUNT],
+        date: Option<&DateTime>,
+        time: Option<u32>,
+    ) -> ([Option<MoldRisk>; DHT_SENSOR_COUNT], Option<MoldSummary>) {
+        let minutes = match (self.last_time, time) {
+            (Some(last_time), Some(time)) if time >= last_time => time - last_time,
+            _ => 0,
+        };
+
+        if time.is_some() {
+            self.last_time = time;
+        }
+
+        let summary = date.and_then(|date| self.finish_day(date));
+
+        for room in (0..DHT_SENSOR_COUNT).filter(|&room| DHT_CHANNELS[room].mold) {
+            if let (Ok(values), true) = (measurements[room], minutes > 0) {
+                let index = &mut self.indexes[room];
+                index.update(values.temperature as i32*10, values.humidity as u32*10, minutes);
+                self.backup.write(MOLD_INDEX + room, index.to_raw());
+            }
+
+            let index = self.indexes[room].index();
+            let maximum = self.daily_maximum[room].map_or(index, |maximum| maximum.max(index));
+            self.daily_maximum[room] = Some(maximum);
+        }
+
+        let risks = core::array::from_fn(|room| match DHT_CHANNELS[room].mold {
+            true => Some(MoldRisk { index: self.indexes[room].index() }),
+            false => None,
+        });
+
+        (risks, summary)
+    }
+
+    fn finish_day(&mut self, date: &DateTime) -> Option<MoldSummary> {
+        let same_day = |other: &DateTime| {
+            (other.year, other.month, other.day) == (date.year, date.month, date.day)
+        };
+
+        let summary = match self.date {
+            Some(ref previous) if !same_day(previous) => Some(MoldSummary {
+                date: *previous,
+                daily_maximum: self.daily_maximum,
+            }),
+            _ => None,
+        };
+
+        if summary.is_some() {
+            self.daily_maximum = [None; DHT_SENSOR_COUNT];
+        }
+
+        self.date = Some(*date);
+        summary
+    }
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+use stm32f4xx_hal::pac;
+
+/// Enter the standby mode with the lowest consumption, only reset
+/// or the WKUP pin starts the logger again
+pub fn enter_standby() -> ! {
+    // Nothing runs after standby, so it is OK to steal the peripherals
+    let dp = unsafe { pac::Peripherals::steal() };
+    let mut cp = unsafe { cortex_m::Peripherals::steal() };
+    dp.RCC.apb1enr.modify(|_, w| w.pwren().set_bit());
+    dp.PWR.cr.modify(|_, w| w.pdds().set_bit().cwuf().set_bit());
+    cp.SCB.set_sleepdeep();
+
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,80 @@
+use core::cell::RefCell;
+use arrayvec::ArrayVec;
+use embedded_hal::adc::Channel;
+use lib_ntc::{NtcConfig, NtcError, resistance_milliohms, temperature_centidegrees};
+use stm32f4xx_hal::{adc::{Adc, config::SampleTime}, pac::ADC1};
+
+use crate::config::MAX_TEMPERATURE_PROBES;
+use super::{TemperatureProbe, ProbeSource, ProbeReader, ProbeError};
+
+/// Highest value of a single 12-bit conversion
+const FULL_SCALE: u32 = 4095;
+
+/// Analog pin that can be sampled by ADC1, allows to keep pins
+/// of different types in a single array
+pub trait AnalogInput {
+    fn sample(&self, adc: &mut Adc<ADC1>) -> u16;
+}
+
+impl<PIN> AnalogInput for PIN
+where PIN: Channel<ADC1, ID = u8> {
+    fn sample(&self, adc: &mut Adc<ADC1>) -> u16 {
+        // Longest sample time, thermistor dividers have high impedance
+        adc.convert(self, SampleTime::Cycles_480)
+    }
+}
+
+/// Probes measured by the internal ADC, `N` thermistors in voltage
+/// dividers with series resistors, the ADC is shared with the supply monitor
+pub struct AnalogProbes<'a, const N: usize> {
+    adc: &'a RefCell<Adc<ADC1>>,
+    ntc_inputs: [&'a dyn AnalogInput; N],
+    ntc_configs: [NtcConfig; N],
+    oversampling: u32,
+}
+
+impl<'a, const N: usize> AnalogProbes<'a, N> {
+    /// Every reading is a sum of `oversampling` conversions
+    pub fn new(
+        adc: &'a RefCell<Adc<ADC1>>,
+        ntc_inputs: [&'a dyn AnalogInput; N],
+        ntc_configs: [NtcConfig; N],
+        oversampling: u32,
+    ) -> Self {
+        Self { adc, ntc_inputs, ntc_configs, oversampling: oversampling.max(1) }
+    }
+
+    fn oversample(&self, input: &dyn AnalogInput) -> u32 {
+        oversample(&mut self.adc.borrow_mut(), input, self.oversampling)
+    }
+}
+
+impl<'a, const N: usize> ProbeReader for AnalogProbes<'a, N> {
+    fn read(&mut self, probes: &mut ArrayVec<TemperatureProbe, MAX_TEMPERATURE_PROBES>) {
+        for index in 0..N {
+            let sample = self.oversample(self.ntc_inputs[index]);
+            let config = &self.ntc_configs[index];
+
+            let temperature = resistance_milliohms(sample, FULL_SCALE*self.oversampling, config)
+                .map(|milliohms| temperature_centidegrees(milliohms, &config.model))
+                .map_err(to_probe_error);
+
+            let _ = probes.try_push(TemperatureProbe {
+                source: ProbeSource::Ntc(index as u8),
+                temperature,
+            });
+        }
+    }
+}
+
+/// Sum of `count` conversions of the `input`
+pub fn oversample(adc: &mut Adc<ADC1>, input: &dyn AnalogInput, count: u32) -> u32 {
+    (0..count).map(|_| input.sample(adc) as u32).sum()
+}
+
+fn to_probe_error(error: NtcError) -> ProbeError {
+    match error {
+        NtcError::OpenCircuit => ProbeError::OpenCircuit,
+        NtcError::ShortCircuit => ProbeError::ShortCircuit,
+    }
+}
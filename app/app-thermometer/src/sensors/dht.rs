@@ -0,0 +1,180 @@
+use core::fmt::Display;
+use cortex_m::peripheral::DWT;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use embedded_hal::blocking::{delay::{DelayUs, DelayMs}};
+
+/// Sensors of the DHT family share the single wire protocol, but differ
+/// in the start pulse length and in the meaning of the transferred data
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum DhtKind {
+    /// DHT11, 1 degree and 1 % resolution, no sub-zero temperatures
+    Dht11,
+    /// DHT22 or AM2302, 0.1 degree and 0.1 % resolution
+    Dht22,
+}
+
+impl DhtKind {
+    fn start_pulse_ms(&self) -> u16 {
+        match self {
+            DhtKind::Dht11 => 20,
+            DhtKind::Dht22 => 2,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct Measurement {
+    /// In 1/10 degrees celsius
+    pub temperature: i16,
+    /// In 1/10 percent of relative humidity
+    pub humidity: u16,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum DhtError {
+    /// Sensor did not answer the start pulse, it is missing or unpowered
+    NoResponse,
+    /// Transfer stopped in the middle, usually a bad cable or contact
+    Timeout,
+    CrcMismatch,
+    Gpio,
+}
+
+impl Display for DhtError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DhtError::NoResponse => write!(f, "NR"),
+            DhtError::Timeout => write!(f, "TO"),
+            DhtError::CrcMismatch => write!(f, "CRC"),
+            DhtError::Gpio => write!(f, "IO"),
+        }
+    }
+}
+
+/// Bit-banged DHT11/DHT22 driver, pulse lengths are measured using
+/// the DWT cycle counter that has to be enabled before reading
+pub struct Dht<T> {
+    pin: T,
+    kind: DhtKind,
+}
+
+impl<T> Dht<T>
+where T: InputPin + OutputPin {
+    pub fn new(pin: T, kind: DhtKind) -> Self {
+        Self { pin, kind }
+    }
+
+    pub fn kind(&self) -> DhtKind {
+        self.kind
+    }
+
+    pub fn perform_measurement<D>(
+        &mut self,
+        delay: &mut D,
+        cycles_per_us: u32,
+    ) -> Result<Measurement, DhtError>
+    where D: DelayUs<u16> + DelayMs<u16> {
+        let data = self.read_raw(delay, cycles_per_us);
+
+        // Release the line so that the sensor stays idle until the next start pulse
+        self.pin.set_high().map_err(|_| DhtError::Gpio)?;
+
+        decode(self.kind, &data?)
+    }
+
+    fn read_raw<D>(
+        &mut self,
+        delay: &mut D,
+        cycles_per_us: u32,
+    ) -> Result<[u8; 5], DhtError>
+    where D: DelayUs<u16> + DelayMs<u16> {
+        self.pin.set_low().map_err(|_| DhtError::Gpio)?;
+        delay.delay_ms(self.kind.start_pulse_ms());
+        self.pin.set_high().map_err(|_| DhtError::Gpio)?;
+
+        // Sensor responds with 80 us low and 80 us high pulse
+        self.wait_for_level(false, 100, cycles_per_us).map_err(no_response)?;
+        self.wait_for_level(true, 100, cycles_per_us).map_err(no_response)?;
+        self.wait_for_level(false, 100, cycles_per_us)?;
+
+        let mut data = [0; 5];
+
+        // Every bit starts with 50 us low pulse followed by high pulse
+        // lasting either 26-28 us (zero) or 70 us (one)
+        for bit in 0..40 {
+            self.wait_for_level(true, 80, cycles_per_us)?;
+            let high_us = self.wait_for_level(false, 100, cycles_per_us)?;
+
+            if high_us > 48 {
+                data[bit/8] |= 0x80 >> (bit % 8);
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Busy wait until the pin reaches the given level, return
+    /// the number of microseconds elapsed
+    fn wait_for_level(
+        &self,
+        high: bool,
+        timeout_us: u32,
+        cycles_per_us: u32,
+    ) -> Result<u32, DhtError> {
+        let start = DWT::cycle_count();
+
+        loop {
+            let elapsed_us = DWT::cycle_count().wrapping_sub(start)/cycles_per_us;
+
+            if self.pin.is_high().map_err(|_| DhtError::Gpio)? == high {
+                return Ok(elapsed_us);
+            }
+
+            if elapsed_us > timeout_us {
+                return Err(DhtError::Timeout);
+            }
+        }
+    }
+}
+
+/// Timeout before the sensor answered means there is no sensor at all
+fn no_response(error: DhtError) -> DhtError {
+    match error {
+        DhtError::Timeout => DhtError::NoResponse,
+        error => error,
+    }
+}
+
+/// Check the checksum and convert the 5 received bytes to measurement
+fn decode(kind: DhtKind, data: &[u8; 5]) -> Result<Measurement, DhtError> {
+    let sum = data[..4].iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+
+    if sum != data[4] {
+        return Err(DhtError::CrcMismatch);
+    }
+
+    let measurement = match kind {
+        DhtKind::Dht11 => {
+            // Integral and decimal parts, newer DHT11 revisions mark
+            // sub-zero temperatures using the highest decimal bit
+            let magnitude = data[2] as i16*10 + (data[3] & 0x7F) as i16;
+
+            Measurement {
+                temperature: if data[3] & 0x80 != 0 { -magnitude } else { magnitude },
+                humidity: data[0] as u16*10 + data[1] as u16,
+            }
+        },
+        DhtKind::Dht22 => {
+            // 16 bit big endian values in tenths, temperature sign is
+            // stored in the highest bit instead of two's complement
+            let magnitude = (((data[2] & 0x7F) as i16) << 8) | data[3] as i16;
+
+            Measurement {
+                temperature: if data[2] & 0x80 != 0 { -magnitude } else { magnitude },
+                humidity: ((data[0] as u16) << 8) | data[1] as u16,
+            }
+        },
+    };
+
+    Ok(measurement)
+}
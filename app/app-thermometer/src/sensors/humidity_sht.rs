@@ -0,0 +1,61 @@
+use arrayvec::ArrayVec;
+use embedded_hal::blocking::{i2c, delay::DelayMs};
+
+use crate::config::{MAX_SHT_SENSORS, SHT_HEATER_HUMIDITY};
+use super::sht::{Sht, ShtKind, ShtMeasurement, ShtError, ADDRESSES};
+
+#[derive(Clone)]
+pub struct ShtReading {
+    pub kind: ShtKind,
+    pub address: u8,
+    pub serial: Option<u32>,
+    pub measurement: Result<ShtMeasurement, ShtError>,
+    /// Heater was pulsed after the measurement to recover from condensation
+    pub heated: bool,
+}
+
+/// SHT3x/SHT4x sensors sharing a single handle to the I2C bus
+pub struct ShtSensors<I2C> {
+    i2c: I2C,
+    sensors: ArrayVec<Sht, MAX_SHT_SENSORS>,
+}
+
+impl<I2C, E> ShtSensors<I2C>
+where I2C: i2c::Write<Error = E> + i2c::Read<Error = E> {
+    /// Register sensors of the given kinds and addresses, addresses outside
+    /// of the SHT range are skipped
+    pub fn new<D: DelayMs<u16>>(mut i2c: I2C, delay: &mut D, config: &[(ShtKind, u8)]) -> Self {
+        let sensors = config.iter()
+            .filter(|(_, address)| ADDRESSES.contains(address))
+            .take(MAX_SHT_SENSORS)
+            .map(|(kind, address)| Sht::new(&mut i2c, delay, *kind, *address))
+            .collect();
+
+        Self { i2c, sensors }
+    }
+
+    /// Measure all sensors, sensors close to saturation are heated
+    /// afterwards, so that condensed water evaporates before the next reading
+    pub fn read<D: DelayMs<u16>>(&mut self, delay: &mut D) -> ArrayVec<ShtReading, MAX_SHT_SENSORS> {
+        let i2c = &mut self.i2c;
+
+        self.sensors.iter_mut().map(|sensor| {
+            let measurement = sensor.measure(i2c, delay);
+
+            let heated = match measurement {
+                Ok(values) if values.humidity >= SHT_HEATER_HUMIDITY => {
+                    sensor.heat(i2c, delay).is_ok()
+                },
+                _ => false,
+            };
+
+            ShtReading {
+                kind: sensor.kind(),
+                address: sensor.address(),
+                serial: sensor.serial(),
+                measurement,
+                heated,
+            }
+        }).collect()
+    }
+}
@@ -0,0 +1,139 @@
+use embedded_hal::blocking::i2c;
+use stm32f4xx_hal::{
+    pac::I2C1,
+    gpio::{gpiob::{PB8, PB9}, Alternate, OpenDrain, PinState},
+    i2c::{I2c, Mode, Error},
+    rcc::Clocks,
+};
+
+type I2cPins = (PB8<Alternate<4, OpenDrain>>, PB9<Alternate<4, OpenDrain>>);
+
+/// A slave finishes the byte it is sending after at most 9 clocks
+const RECOVERY_CLOCKS: u8 = 9;
+
+/// Half period of the recovery clock in microseconds, 100 kHz
+const RECOVERY_HALF_PERIOD_US: u32 = 5;
+
+/// STOP takes a single clock period, 10 us at 100 kHz, it never finishes
+/// while a slave holds SDA low
+const STOP_TIMEOUT_US: u32 = 100;
+
+/// I2C1 on PB8 (SCL) and PB9 (SDA) releasing the bus held by a slave before
+/// the next transaction, e.g. after the MCU was reset in the middle of a read
+pub struct RecoverableI2c {
+    /// Always present outside of the recovery
+    i2c: Option<I2c<I2C1, I2cPins>>,
+    mode: Mode,
+    clocks: Clocks,
+    recoveries: u32,
+}
+
+impl RecoverableI2c {
+    pub fn new(i2c: I2C1, pins: I2cPins, mode: impl Into<Mode>, clocks: &Clocks) -> Self {
+        let mode = mode.into();
+
+        Self {
+            i2c: Some(I2c::new(i2c, pins, mode, clocks)),
+            mode,
+            clocks: *clocks,
+            recoveries: 0,
+        }
+    }
+
+    /// Number of recoveries since startup
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+
+    fn bus(&mut self) -> &mut I2c<I2C1, I2cPins> {
+        if self.is_stuck() {
+            self.recover();
+        }
+
+        self.i2c.as_mut().unwrap()
+    }
+
+    /// Clock out the byte the slave is sending, issue a STOP and
+    /// initialize the peripheral again
+    fn recover(&mut self) {
+        if let Some(i2c) = self.i2c.take() {
+            let (i2c, (scl, sda)) = i2c.release();
+            let mut scl = scl.into_open_drain_output_in_state(PinState::High);
+            let mut sda = sda.into_open_drain_output_in_state(PinState::High);
+            let half_period = self.clocks.hclk().to_MHz()*RECOVERY_HALF_PERIOD_US;
+
+            for _ in 0..RECOVERY_CLOCKS {
+                if sda.is_high() {
+                    break;
+                }
+
+                scl.set_low();
+                cortex_m::asm::delay(half_period);
+                scl.set_high();
+                cortex_m::asm::delay(half_period);
+            }
+
+            // SDA rising while SCL is high
+            scl.set_low();
+            cortex_m::asm::delay(half_period);
+            sda.set_low();
+            cortex_m::asm::delay(half_period);
+            scl.set_high();
+            cortex_m::asm::delay(half_period);
+            sda.set_high();
+            cortex_m::asm::delay(half_period);
+
+            let pins = (
+                scl.into_alternate::<4>().set_open_drain(),
+                sda.into_alternate::<4>().set_open_drain(),
+            );
+            self.i2c = Some(I2c::new(i2c, pins, self.mode, &self.clocks));
+            self.recoveries += 1;
+        }
+    }
+
+    /// Peripheral sees the bus busy between transactions when a slave holds
+    /// SDA low, a START would never be generated, a STOP that does not
+    /// finish in time means the same
+    fn is_stuck(&self) -> bool {
+        // The HAL gives no access to the registers of the wrapped peripheral,
+        // they are read only while `self.i2c` owns I2C1
+        let registers = unsafe { &*I2C1::ptr() };
+        let microsecond = self.clocks.hclk().to_MHz();
+
+        // STOP of the previous transaction is still being generated
+        for _ in 0..STOP_TIMEOUT_US {
+            if registers.cr1.read().stop().bit_is_clear() {
+                return registers.sr2.read().busy().bit_is_set();
+            }
+
+            cortex_m::asm::delay(microsecond);
+        }
+
+        true
+    }
+}
+
+impl i2c::Write for RecoverableI2c {
+    type Error = Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Error> {
+        self.bus().write(address, bytes)
+    }
+}
+
+impl i2c::Read for RecoverableI2c {
+    type Error = Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Error> {
+        self.bus().read(address, buffer)
+    }
+}
+
+impl i2c::WriteRead for RecoverableI2c {
+    type Error = Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Error> {
+        self.bus().write_read(address, bytes, buffer)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use embedded_hal::{blocking::spi, digital::v2::OutputPin};
+
+use super::ProbeError;
+
+/// MAX31855 K-type thermocouple converter, read-only SPI device
+/// working in SPI mode 1
+pub struct Max31855<CS> {
+    cs: CS,
+}
+
+impl<CS> Max31855<CS>
+where CS: OutputPin {
+    pub fn new(mut cs: CS) -> Self {
+        let _ = cs.set_high();
+        Self { cs }
+    }
+
+    /// Read thermocouple temperature in 1/100 degrees celsius
+    pub fn read<SPI, E>(&mut self, spi: &mut SPI) -> Result<i32, ProbeError>
+    where SPI: spi::Transfer<u8, Error = E> {
+        let mut data = [0; 4];
+        self.cs.set_low().map_err(|_| ProbeError::Bus)?;
+        let result = spi.transfer(&mut data).map(|_| ()).map_err(|_| ProbeError::Bus);
+        self.cs.set_high().map_err(|_| ProbeError::Bus)?;
+        result?;
+
+        decode(u32::from_be_bytes(data))
+    }
+}
+
+fn decode(frame: u32) -> Result<i32, ProbeError> {
+    if frame & (1 << 16) != 0 {
+        return Err(match frame & 0b111 {
+            0b001 => ProbeError::OpenCircuit,
+            0b010 => ProbeError::ShortToGnd,
+            _ => ProbeError::ShortToVcc,
+        });
+    }
+
+    if frame == 0 || frame == u32::MAX {
+        // Idle bus, converter not connected
+        return Err(ProbeError::NotResponding);
+    }
+
+    // 14-bit signed value in 0.25 degree steps in the highest bits
+    let raw = (frame as i32) >> 18;
+    Ok(raw*25)
+}
@@ -0,0 +1,189 @@
+use embedded_hal::{blocking::spi, digital::v2::OutputPin};
+
+use super::ProbeError;
+
+const REGISTER_CONFIG: u8 = 0x00;
+const REGISTER_HIGH_FAULT_THRESHOLD: u8 = 0x03;
+const WRITE: u8 = 0x80;
+
+const CONFIG_VBIAS: u8 = 0x80;
+const CONFIG_AUTO_CONVERSION: u8 = 0x40;
+const CONFIG_THREE_WIRE: u8 = 0x10;
+const CONFIG_CLEAR_FAULT: u8 = 0x02;
+const CONFIG_FILTER_50HZ: u8 = 0x01;
+
+/// RTD ratio thresholds (15 bits shifted left by one), values
+/// outside indicate open or shorted RTD
+const HIGH_FAULT_THRESHOLD: u16 = 0x7F00 << 1;
+const LOW_FAULT_THRESHOLD: u16 = 0x0100 << 1;
+
+pub struct RtdConfig {
+    /// Nominal resistance at 0 degrees, 100 for PT100, 1000 for PT1000
+    pub nominal_ohms: u32,
+    /// Reference resistor on the board
+    pub reference_ohms: u32,
+    pub three_wire: bool,
+    /// Reject 50 Hz mains noise instead of 60 Hz
+    pub filter_50hz: bool,
+}
+
+/// MAX31865 RTD converter in continuous conversion mode, SPI mode 1
+pub struct Max31865<CS> {
+    cs: CS,
+    config: RtdConfig,
+    configured: bool,
+}
+
+impl<CS> Max31865<CS>
+where CS: OutputPin {
+    pub fn new(mut cs: CS, config: RtdConfig) -> Self {
+        let _ = cs.set_high();
+        Self { cs, config, configured: false }
+    }
+
+    /// Read RTD temperature in 1/100 degrees celsius
+    pub fn read<SPI, E>(&mut self, spi: &mut SPI) -> Result<i32, ProbeError>
+    where SPI: spi::Transfer<u8, Error = E> {
+        if !self.configured {
+            self.configure(spi)?;
+            self.configured = true;
+        }
+
+        // Config, RTD, high and low fault thresholds and fault status
+        let mut data = [0; 9];
+        data[0] = REGISTER_CONFIG;
+        self.transfer(spi, &mut data)?;
+
+        if data[1] & !CONFIG_CLEAR_FAULT != self.config_register() {
+            // Converter was reset or is not connected
+            self.configured = false;
+            return Err(ProbeError::NotResponding);
+        }
+
+        let rtd = u16::from_be_bytes([data[2], data[3]]);
+
+        if rtd & 0x01 != 0 {
+            let _ = self.write_register(spi, REGISTER_CONFIG, self.config_register() | CONFIG_CLEAR_FAULT);
+            return Err(decode_fault(data[8]));
+        }
+
+        Ok(rtd_to_centidegrees(rtd >> 1, self.config.reference_ohms, self.config.nominal_ohms))
+    }
+
+    fn configure<SPI, E>(&mut self, spi: &mut SPI) -> Result<(), ProbeError>
+    where SPI: spi::Transfer<u8, Error = E> {
+        let [high_msb, high_lsb] = HIGH_FAULT_THRESHOLD.to_be_bytes();
+        let [low_msb, low_lsb] = LOW_FAULT_THRESHOLD.to_be_bytes();
+        let mut thresholds = [REGISTER_HIGH_FAULT_THRESHOLD | WRITE, high_msb, high_lsb, low_msb, low_lsb];
+        self.transfer(spi, &mut thresholds)?;
+        self.write_register(spi, REGISTER_CONFIG, self.config_register() | CONFIG_CLEAR_FAULT)
+    }
+
+    fn config_register(&self) -> u8 {
+        let mut config = CONFIG_VBIAS | CONFIG_AUTO_CONVERSION;
+
+        if self.config.three_wire {
+            config |= CONFIG_THREE_WIRE;
+        }
+
+        if self.config.filter_50hz {
+            config |= CONFIG_FILTER_50HZ;
+        }
+
+        config
+    }
+
+    fn write_register<SPI, E>(&mut self, spi: &mut SPI, register: u8, value: u8) -> Result<(), ProbeError>
+    where SPI: spi::Transfer<u8, Error = E> {
+        self.transfer(spi, &mut [register | WRITE, value])
+    }
+
+    /// First byte is the register address, registers are read
+    /// or written sequentially
+    fn transfer<SPI, E>(&mut self, spi: &mut SPI, data: &mut [u8]) -> Result<(), ProbeError>
+    where SPI: spi::Transfer<u8, Error = E> {
+        self.cs.set_low().map_err(|_| ProbeError::Bus)?;
+        let result = spi.transfer(data).map(|_| ()).map_err(|_| ProbeError::Bus);
+        self.cs.set_high().map_err(|_| ProbeError::Bus)?;
+        result
+    }
+}
+
+fn decode_fault(status: u8) -> ProbeError {
+    if status & 0x80 != 0 {
+        // RTD ratio above the high threshold
+        ProbeError::OpenCircuit
+    } else if status & 0x40 != 0 {
+        ProbeError::ShortCircuit
+    } else if status & 0x38 != 0 {
+        // REFIN or RTDIN voltage out of range, broken force wire
+        ProbeError::OpenCircuit
+    } else {
+        ProbeError::OverUnderVoltage
+    }
+}
+
+/// Callendar-Van Dusen coefficients scaled by 1e15
+const CVD_A: i128 = 3_908_300_000_000;
+const CVD_B: i128 = -577_500_000;
+const CVD_C: i128 = -4_183;
+const CVD_SCALE: i128 = 1_000_000_000_000_000;
+
+/// Convert 15-bit RTD to reference resistance ratio to 1/100 degrees
+/// celsius using Callendar-Van Dusen equation in fixed point
+pub fn rtd_to_centidegrees(rtd: u16, reference_ohms: u32, nominal_ohms: u32) -> i32 {
+    // Resistance relative to the nominal resistance as a fraction
+    let numerator = rtd as i128*reference_ohms as i128;
+    let denominator = 32768*nominal_ohms as i128;
+
+    // Above zero, R/R0 = 1 + A*T + B*T^2, solved as quadratic equation
+    // with the discriminant (A^2 - 4B + 4B*R/R0) scaled by 1e20
+    let discriminant = 1_758_480_889_000_000 - 231_000_000_000_000*numerator/denominator;
+    let root = isqrt(discriminant.max(0) as u128) as i128;
+    let centidegrees = ((39_083_000 - root)*4 + 231)/462;
+
+    if numerator >= denominator {
+        return centidegrees as i32;
+    }
+
+    // Below zero, the C*(T - 100)*T^3 term is added, the quadratic
+    // solution is refined using Newton's method in 1/1000 degrees
+    let mut millidegrees = centidegrees*10;
+    let offset = CVD_SCALE - numerator*CVD_SCALE/denominator;
+
+    for _ in 0..4 {
+        let t = millidegrees;
+        let value = CVD_A*t/1_000
+            + CVD_B*t*t/1_000_000
+            + CVD_C*(t - 100_000)*t*t*t/1_000_000_000_000
+            + offset;
+        let derivative = CVD_A/1_000
+            + 2*CVD_B*t/1_000_000
+            + CVD_C*(4*t*t*t - 300_000*t*t)/1_000_000_000_000;
+        millidegrees -= value/derivative;
+    }
+
+    // Division truncates towards zero, so the half is added away from zero
+    let rounded = match millidegrees < 0 {
+        true => (millidegrees - 5)/10,
+        false => (millidegrees + 5)/10,
+    };
+
+    rounded as i32
+}
+
+fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    let mut root = value;
+    let mut next = (root + 1)/2;
+
+    while next < root {
+        root = next;
+        next = (root + value/root)/2;
+    }
+
+    root
+}
@@ -1,18 +1,95 @@
-mod temperature_dht11;
+mod dht;
+mod temperature_dht;
+mod onewire_pin;
+mod temperature_ds18b20;
+mod sht;
+mod humidity_sht;
+mod max31855;
+mod max31865;
+mod temperature_spi;
+mod analog;
+mod scd4x;
+mod particulate_pms5003;
+mod supply;
+mod scan;
+mod i2c_recovery;
+mod shared_i2c;
+mod pressure_bmx280;
 
-use core::fmt::Display;
+use core::{cell::RefCell, fmt::Display};
 
-use bmp280_rs::{BMP280, I2CAddress};
-use dht11::{Measurement};
+use arrayvec::ArrayVec;
 use pcf8563::{PCF8563, DateTime};
 use embedded_hal::blocking::{i2c, delay::{DelayUs, DelayMs}};
+use lib_onewire::Address;
+use lib_health::{Hold, Held};
+use lib_weather::{
+    PressureHistory, PressureTendency, Forecast, Psychrometrics, sea_level_pressure, zambretti,
+    psychrometrics
+};
 
-pub use temperature_dht11::{Dht11Drivers, Dht11Reader};
+use crate::ventilation::{VentilationAdvisor, Ventilation};
+use crate::mold::{MoldTracker, MoldRisk, MoldSummary};
+use crate::health::{HealthMonitor, SensorHealth, bmx280_fault};
+use crate::calibration::{Calibrations, RawValues};
+use crate::config::{
+    DHT_SENSOR_COUNT, MAX_TEMPERATURE_PROBES, MAX_SHT_SENSORS, CO2_ALARM_PPM, STATION_ALTITUDE,
+    PRESSURE_HISTORY_LENGTH
+};
 
+pub use dht::{Dht, DhtKind, Measurement, DhtError};
+pub use temperature_dht::{DhtDrivers, DhtReader, DhtChannel};
+pub use onewire_pin::OneWirePin;
+pub use temperature_ds18b20::Ds18b20Probes;
+pub use temperature_spi::SpiProbes;
+pub use max31855::Max31855;
+pub use max31865::{Max31865, RtdConfig};
+pub use analog::AnalogProbes;
+pub use sht::{ShtKind, ShtMeasurement, ShtError, ADDRESS_DEFAULT as SHT_ADDRESS_DEFAULT, ADDRESSES as SHT_ADDRESSES};
+pub use humidity_sht::{ShtSensors, ShtReading};
+pub use scd4x::{Scd4x, Co2Measurement};
+pub use particulate_pms5003::{Pms5003, Pms5003Config, Pms5003Mode, ParticulateReader};
+pub use lib_pms5003::Measurement as ParticulateMatter;
+pub use lib_bmx280::{
+    Bmx280Profile, Measurement as TemperaturePressure,
+    ADDRESS_SDO_GROUNDED as BMX280_ADDRESS_SDO_GROUNDED
+};
+pub use supply::{SupplyMonitor, SupplyConfig, SupplyVoltage, VoltageDivider};
+pub use scan::{I2cScan, MAX_I2C_DEVICES};
+pub use i2c_recovery::RecoverableI2c;
+pub use shared_i2c::I2cProxy;
+pub use pressure_bmx280::Bmx280Sensor;
+
+#[derive(Clone)]
 pub struct Sensors {
     pub time: Option<DateTime>,
     pub temperature_pressure: Option<TemperaturePressure>,
-    pub temperature_humidity: [Option<Measurement>; 6],
+    /// Station pressure reduced to the sea level in pascals
+    pub sea_level_pressure: Option<i32>,
+    pub pressure_tendency: Option<PressureTendency>,
+    pub forecast: Option<Forecast>,
+    /// Failed reading is replaced by the previous one within the staleness window
+    pub temperature_humidity: [Result<Held<Measurement>, DhtError>; DHT_SENSOR_COUNT],
+    /// Values derived from every DHT measurement
+    pub temperature_humidity_derived: [Option<Psychrometrics>; DHT_SENSOR_COUNT],
+    /// Mold index of the rooms measured by DHT sensors
+    pub mold: [Option<MoldRisk>; DHT_SENSOR_COUNT],
+    /// Mold figures of the previous day, present on the first reading of a day
+    pub mold_summary: Option<MoldSummary>,
+    pub temperature_probes: ArrayVec<TemperatureProbe, MAX_TEMPERATURE_PROBES>,
+    pub sht_sensors: ArrayVec<ShtReading, MAX_SHT_SENSORS>,
+    pub co2: Option<Co2Measurement>,
+    pub particulate_matter: Option<ParticulateMatter>,
+    pub supply: SupplyVoltage,
+    /// Advice of the ventilation advisor, if configured
+    pub ventilation: Option<Ventilation>,
+    pub health: SensorHealth,
+    /// Values before calibration
+    pub raw: RawValues,
+    /// Number of times the I2C bus was released since startup
+    pub i2c_recoveries: u32,
+    /// MCU die temperature in 1/100 degrees celsius, always available
+    pub mcu_temperature: i32,
 }
 
 impl Sensors {
@@ -23,6 +100,11 @@ impl Sensors {
             seconds: date_time.seconds,
         })
     }
+
+    /// CO2 concentration reached the configured alarm threshold
+    pub fn co2_alarm(&self) -> bool {
+        self.co2.map_or(false, |values| values.co2 >= CO2_ALARM_PPM)
+    }
 }
 
 #[derive(PartialEq, Eq, Copy, Clone)]
@@ -48,59 +130,200 @@ impl Display for Time {
     }
 }
 
-pub fn read_sensors<I2C, I2CE, D>(
-    i2c: I2C,
-    thermo_drivers: &mut dyn Dht11Reader<D>,
+pub fn read_sensors<I2C, E, D>(
+    i2c_bus: &RefCell<RecoverableI2c>,
+    time_driver: &mut PCF8563<I2C>,
+    bmx280: &mut Bmx280Sensor<I2C>,
+    pressure_history: &mut PressureHistory<PRESSURE_HISTORY_LENGTH>,
+    thermo_drivers: &mut dyn DhtReader<D, DHT_SENSOR_COUNT>,
+    dht_holds: &mut [Hold<Measurement>; DHT_SENSOR_COUNT],
+    probe_readers: &mut [&mut dyn ProbeReader],
+    sht_sensors: &mut ShtSensors<I2C>,
+    co2_sensor: &mut Scd4x<I2C>,
+    particulate_sensor: &mut dyn ParticulateReader<D>,
+    supply_monitor: &mut SupplyMonitor,
+    ventilation: Option<&mut VentilationAdvisor>,
+    mold_tracker: &mut MoldTracker,
+    health_monitor: &mut HealthMonitor,
+    calibrations: &Calibrations,
     delay: &mut D
-) -> (Sensors, I2C)
+) -> Sensors
 where
-    I2C: i2c::Write<Error = I2CE> + i2c::WriteRead<Error = I2CE>,
-    I2CE: core::fmt::Debug,
+    I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E> + i2c::Read<Error = E>,
+    E: core::fmt::Debug,
     D: DelayUs<u16> + DelayMs<u16>,
 {
-    let mut time_driver = PCF8563::new(i2c);
+    // Measured first, before the sensors load the supply
+    let supply = supply_monitor.read();
+    let mcu_temperature = supply_monitor.read_die_temperature(supply.supply);
     let time = time_driver.get_datetime().ok();
-    let mut i2c = time_driver.destroy();
-    let temperature_pressure = read_bmp280(&mut i2c);
+    let mut raw = RawValues::new();
+    let bmx280_result = bmx280.read(delay)
+        .map(|values| raw.calibrate_bmx280(calibrations, values))
+        .map_err(|error| bmx280_fault(&error));
+    let temperature_pressure = bmx280_result.ok();
 
-    let temperature_humidity = thermo_drivers.read(delay);
+    let sea_level_pressure = temperature_pressure.as_ref()
+        .map(|values| sea_level_pressure(values.pressure, STATION_ALTITUDE));
 
-    let sensors = Sensors {
-        time,
-        temperature_pressure,
-        temperature_humidity
+    if let (Some(time), Some(pressure)) = (time.as_ref(), sea_level_pressure) {
+        pressure_history.record(minutes_since_2000(time), pressure);
+    }
+
+    let pressure_tendency = pressure_history.tendency();
+
+    // There is no wind vane on the board
+    let forecast = match (time.as_ref(), sea_level_pressure, pressure_tendency) {
+        (Some(time), Some(pressure), Some(tendency)) =>
+            Some(zambretti(pressure, tendency.trend, time.month, None)),
+        _ => None,
     };
 
-    (sensors, i2c)
+    let mut sht_sensors = sht_sensors.read(delay);
+
+    for reading in sht_sensors.iter_mut() {
+        let address = reading.address;
+        reading.measurement = reading.measurement
+            .map(|values| raw.calibrate_sht(calibrations, address, values));
+    }
+
+    let pressure = temperature_pressure.as_ref().map(|values| values.pressure);
+    let co2 = co2_sensor.read(delay, pressure);
+
+    let seconds = time.as_ref().map(seconds_since_2000);
+    let mut dht_readings = thermo_drivers.read(delay);
+
+    for (index, reading) in dht_readings.iter_mut().enumerate() {
+        *reading = reading.map(|values| raw.calibrate_dht(calibrations, index, values));
+    }
+
+    let temperature_humidity: [_; DHT_SENSOR_COUNT] = core::array::from_fn(|index| {
+        dht_holds[index].update(dht_readings[index], seconds)
+    });
+    let dht_values = temperature_humidity.map(|reading| reading.map(|held| held.value));
+
+    let temperature_humidity_derived = dht_values.map(|values| values.ok().map(|values| {
+        psychrometrics(values.temperature as i32*10, values.humidity as u32*10)
+    }));
+    let particulate_matter = particulate_sensor.read(delay);
+    let minutes = time.as_ref().map(minutes_since_2000);
+    let (mold, mold_summary) = mold_tracker.update(&dht_values, time.as_ref(), minutes);
+
+    let mut temperature_probes = ArrayVec::new();
+
+    for probe_reader in probe_readers.iter_mut() {
+        probe_reader.read(&mut temperature_probes);
+    }
+
+    let health = health_monitor.update(
+        &dht_readings,
+        bmx280_result.as_ref().map_err(|fault| *fault),
+        co2.as_ref(),
+        particulate_matter.as_ref(),
+        &temperature_probes,
+        &sht_sensors,
+        &supply,
+        seconds,
+    );
+
+    let ventilation = ventilation.and_then(|advisor| advisor.update(
+        &dht_values,
+        &temperature_humidity_derived,
+        minutes,
+    ));
+
+    Sensors {
+        time,
+        temperature_pressure,
+        sea_level_pressure,
+        pressure_tendency,
+        forecast,
+        temperature_humidity,
+        temperature_humidity_derived,
+        mold,
+        mold_summary,
+        temperature_probes,
+        sht_sensors,
+        co2,
+        particulate_matter,
+        supply,
+        mcu_temperature,
+        ventilation,
+        health,
+        raw,
+        i2c_recoveries: i2c_bus.borrow().recoveries(),
+    }
 }
 
-pub struct TemperaturePressure {
+/// Temperature-only sensor discovered or configured at runtime
+#[derive(Clone)]
+pub struct TemperatureProbe {
+    pub source: ProbeSource,
     /// In 1/100 degrees celsius
-    pub temperature: i32,
-    /// In pascals
-    pub pressure: i32,
+    pub temperature: Result<i32, ProbeError>,
 }
 
-fn read_bmp280<I2C, I2CE>(i2c: &mut I2C) -> Option<TemperaturePressure>
-where
-    I2C: i2c::Write<Error = I2CE> + i2c::WriteRead<Error = I2CE>,
-    I2CE: core::fmt::Debug
-{
-    let config = bmp280_rs::Config::handheld_device_dynamic();
-
-    match BMP280::new(i2c, I2CAddress::SdoGrounded, config) {
-        Ok(mut driver) => {
-            driver.trigger_measurement(i2c).ok()?;
-            let raw_pressure = driver.read_pressure(i2c).ok()?;
-            let temperature = driver.read_temperature(i2c).ok()?;
-
-            let values = TemperaturePressure {
-                temperature,
-                pressure: raw_pressure/256,
-            };
-
-            Some(values)
-        },
-        Err(_error) => None,
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum ProbeSource {
+    Ds18b20(Address),
+    Thermocouple,
+    Rtd,
+    /// Thermistor with the given index
+    Ntc(u8),
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum ProbeError {
+    /// Communication with the probe or converter failed
+    Bus,
+    NotResponding,
+    CrcMismatch,
+    /// Probe returned its power-on value instead of a measurement
+    NotConverted,
+    OpenCircuit,
+    ShortCircuit,
+    ShortToGnd,
+    ShortToVcc,
+    OverUnderVoltage,
+}
+
+impl Display for ProbeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProbeError::Bus => write!(f, "Bus"),
+            ProbeError::NotResponding => write!(f, "NR"),
+            ProbeError::CrcMismatch => write!(f, "CRC"),
+            ProbeError::NotConverted => write!(f, "NC"),
+            ProbeError::OpenCircuit => write!(f, "OC"),
+            ProbeError::ShortCircuit => write!(f, "SC"),
+            ProbeError::ShortToGnd => write!(f, "SCG"),
+            ProbeError::ShortToVcc => write!(f, "SCV"),
+            ProbeError::OverUnderVoltage => write!(f, "OV"),
+        }
     }
 }
+
+/// Source of temperature probe readings, every reader appends
+/// the probes it manages
+pub trait ProbeReader {
+    fn read(&mut self, probes: &mut ArrayVec<TemperatureProbe, MAX_TEMPERATURE_PROBES>);
+}
+
+/// Monotonic second counter for the sensor health statistics
+fn seconds_since_2000(time: &DateTime) -> u32 {
+    minutes_since_2000(time)*60 + time.seconds as u32
+}
+
+/// Monotonic minute counter for the pressure history, the RTC keeps
+/// only two digits of the year
+fn minutes_since_2000(time: &DateTime) -> u32 {
+    const DAYS_BEFORE_MONTH: [u32; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+    let year = time.year as u32;
+    let month = (time.month as usize).clamp(1, 12);
+    let leap_day = (year % 4 == 0 && month > 2) as u32;
+    let days = 365*year + (year + 3)/4 + DAYS_BEFORE_MONTH[month - 1] + leap_day
+        + (time.day as u32).saturating_sub(1);
+
+    (days*24 + time.hours as u32)*60 + time.minutes as u32
+}
\ No newline at end of file
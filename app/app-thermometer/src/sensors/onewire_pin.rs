@@ -0,0 +1,65 @@
+use core::fmt::Debug;
+use cortex_m::peripheral::DWT;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use lib_onewire::OneWireBus;
+
+/// 1-Wire bus on an open drain pin with external pull-up, slot timing
+/// is measured using the DWT cycle counter
+pub struct OneWirePin<T> {
+    pin: T,
+    cycles_per_us: u32,
+}
+
+impl<T, E> OneWirePin<T>
+where T: InputPin<Error = E> + OutputPin<Error = E> {
+    /// `cycles_per_us` is the DWT cycle counter (core clock) frequency in MHz
+    pub fn new(pin: T, cycles_per_us: u32) -> Self {
+        Self { pin, cycles_per_us }
+    }
+
+    fn wait_us(&self, us: u32) {
+        let start = DWT::cycle_count();
+        while DWT::cycle_count().wrapping_sub(start) < us*self.cycles_per_us { }
+    }
+}
+
+impl<T, E> OneWireBus for OneWirePin<T>
+where T: InputPin<Error = E> + OutputPin<Error = E>, E: Debug {
+    type Error = E;
+
+    fn reset(&mut self) -> Result<bool, E> {
+        self.pin.set_low()?;
+        self.wait_us(480);
+        self.pin.set_high()?;
+        self.wait_us(70);
+        let presence = self.pin.is_low()?;
+        self.wait_us(410);
+        Ok(presence)
+    }
+
+    fn write_bit(&mut self, bit: bool) -> Result<(), E> {
+        self.pin.set_low()?;
+
+        if bit {
+            self.wait_us(6);
+            self.pin.set_high()?;
+            self.wait_us(64);
+        } else {
+            self.wait_us(60);
+            self.pin.set_high()?;
+            self.wait_us(10);
+        }
+
+        Ok(())
+    }
+
+    fn read_bit(&mut self) -> Result<bool, E> {
+        self.pin.set_low()?;
+        self.wait_us(6);
+        self.pin.set_high()?;
+        self.wait_us(9);
+        let bit = self.pin.is_high()?;
+        self.wait_us(55);
+        Ok(bit)
+    }
+}
@@ -0,0 +1,151 @@
+use embedded_hal::serial;
+use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::blocking::delay::{DelayUs, DelayMs};
+use lib_pms5003::{Parser, Command, Measurement};
+
+/// Interval of polling the UART while waiting for a frame
+const POLL_INTERVAL_US: u16 = 100;
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Pms5003Mode {
+    /// Data frame is requested by a command in every reading
+    Passive,
+    /// Sensor sends frames on its own, the reading waits for the next one
+    Active,
+}
+
+pub struct Pms5003Config {
+    pub mode: Pms5003Mode,
+    /// Maximum time to wait for a data frame
+    pub timeout_ms: u32,
+    /// Number of readings in a single measurement cycle, the sensor sleeps
+    /// for most of the cycle to save the fan and the laser, zero keeps
+    /// the sensor always running
+    pub cycle: u32,
+    /// Number of readings the sensor is running before the value at the end
+    /// of the cycle is taken, the fan needs about 30 seconds to settle
+    pub warmup: u32,
+}
+
+/// Plantower PMS5003 on a UART, `SET` pin of the sensor is used to put
+/// it to sleep
+pub struct Pms5003<S, P> {
+    serial: S,
+    sleep_pin: P,
+    config: Pms5003Config,
+    parser: Parser,
+    configured: bool,
+    cycle_position: u32,
+    last_measurement: Option<Measurement>,
+}
+
+impl<S, P> Pms5003<S, P>
+where S: serial::Read<u8> + serial::Write<u8>, P: OutputPin {
+    pub fn new(serial: S, sleep_pin: P, config: Pms5003Config) -> Self {
+        Self {
+            serial,
+            sleep_pin,
+            config,
+            parser: Parser::new(),
+            configured: false,
+            cycle_position: 0,
+            last_measurement: None,
+        }
+    }
+
+    /// Stop the fan and the laser with a command, the sleep pin is not
+    /// driven when the MCU is in standby
+    pub fn sleep(&mut self) {
+        let _ = self.send(Command::Sleep);
+    }
+
+    fn set_awake(&mut self, awake: bool) {
+        let _ = match awake {
+            true => self.sleep_pin.set_high(),
+            false => self.sleep_pin.set_low(),
+        };
+
+        if !awake {
+            // Operating mode is sent again after wakeup
+            self.configured = false;
+        }
+    }
+
+    fn send(&mut self, command: Command) -> bool {
+        command.frame().iter()
+            .all(|&byte| nb::block!(self.serial.write(byte)).is_ok())
+    }
+
+    fn receive<D>(&mut self, delay: &mut D) -> Option<Measurement>
+    where D: DelayUs<u16> {
+        if !self.configured {
+            let mode = match self.config.mode {
+                Pms5003Mode::Passive => Command::PassiveMode,
+                Pms5003Mode::Active => Command::ActiveMode,
+            };
+
+            self.configured = self.send(mode);
+        }
+
+        self.parser = Parser::new();
+
+        if self.config.mode == Pms5003Mode::Passive && !self.send(Command::ReadPassive) {
+            return None;
+        }
+
+        let polls = self.config.timeout_ms*1000/POLL_INTERVAL_US as u32;
+
+        for _ in 0..polls {
+            match self.serial.read() {
+                Ok(byte) => {
+                    if let Some(Ok(measurement)) = self.parser.push(byte) {
+                        return Some(measurement);
+                    }
+                },
+                Err(nb::Error::WouldBlock) => delay.delay_us(POLL_INTERVAL_US),
+                // Overrun while the main loop was busy, the parser resynchronizes
+                Err(nb::Error::Other(_)) => {},
+            }
+        }
+
+        // Sensor may have been power cycled, configure it again next time
+        self.configured = false;
+        None
+    }
+}
+
+pub trait ParticulateReader<D>
+where D: DelayUs<u16> + DelayMs<u16> {
+    /// Return the latest measurement, the value is kept while the sensor sleeps
+    fn read(&mut self, delay: &mut D) -> Option<Measurement>;
+}
+
+impl<S, P, D> ParticulateReader<D> for Pms5003<S, P>
+where
+S: serial::Read<u8> + serial::Write<u8>,
+P: OutputPin,
+D: DelayUs<u16> + DelayMs<u16> {
+    fn read(&mut self, delay: &mut D) -> Option<Measurement> {
+        if self.config.cycle == 0 {
+            self.set_awake(true);
+            self.last_measurement = self.receive(delay);
+            return self.last_measurement;
+        }
+
+        let position = self.cycle_position;
+        self.cycle_position = (position + 1) % self.config.cycle;
+        let warmup_start = self.config.cycle.saturating_sub(self.config.warmup + 1);
+
+        if position < warmup_start {
+            self.set_awake(false);
+        } else if position + 1 < self.config.cycle {
+            self.set_awake(true);
+        } else {
+            self.set_awake(true);
+            self.last_measurement = self.receive(delay);
+            self.set_awake(false);
+        }
+
+        self.last_measurement
+    }
+}
\ No newline at end of file
@@ -0,0 +1,20 @@
+use core::fmt::Debug;
+use embedded_hal::blocking::{i2c, delay::DelayMs};
+use lib_bmx280::{Bmx280, Bmx280Error, Bmx280Profile, Measurement};
+
+/// BMP280/BME280 with its own handle to the I2C bus
+pub struct Bmx280Sensor<I2C> {
+    i2c: I2C,
+    driver: Bmx280,
+}
+
+impl<I2C, E> Bmx280Sensor<I2C>
+where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug {
+    pub fn new(i2c: I2C, address: u8, profile: Bmx280Profile) -> Self {
+        Self { i2c, driver: Bmx280::new(address, profile) }
+    }
+
+    pub fn read<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Measurement, Bmx280Error<E>> {
+        self.driver.read(&mut self.i2c, delay)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+use core::fmt::Display;
+
+use arrayvec::ArrayVec;
+use embedded_hal::blocking::{i2c, delay::DelayMs};
+use lib_bmx280::{Bmx280Chip, ADDRESS_SDO_GROUNDED, ADDRESS_SDO_HIGH};
+
+use super::sht::{Sht, ShtKind, ADDRESSES as SHT_ADDRESSES};
+use super::scd4x::ADDRESS as SCD4X_ADDRESS;
+use crate::config::MAX_SHT_SENSORS;
+
+const PCF8563_ADDRESS: u8 = 0x51;
+const BMX280_CHIP_ID_REGISTER: u8 = 0xD0;
+
+/// Addresses outside are reserved by the I2C specification
+const FIRST_ADDRESS: u8 = 0x08;
+const LAST_ADDRESS: u8 = 0x77;
+
+/// Devices beyond the limit are not reported
+pub const MAX_I2C_DEVICES: usize = 8;
+
+/// Chip identified by its address and, where it has one, its chip ID
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum I2cChip {
+    Pcf8563,
+    Bmx280(Bmx280Chip),
+    Sht(ShtKind),
+    Scd4x,
+    /// Device acknowledged its address but was not recognized
+    Unknown,
+}
+
+impl Display for I2cChip {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            I2cChip::Pcf8563 => write!(f, "PCF8563"),
+            I2cChip::Bmx280(Bmx280Chip::Bmp280) => write!(f, "BMP280"),
+            I2cChip::Bmx280(Bmx280Chip::Bme280) => write!(f, "BME280"),
+            I2cChip::Sht(ShtKind::Sht3x) => write!(f, "SHT3x"),
+            I2cChip::Sht(ShtKind::Sht4x) => write!(f, "SHT4x"),
+            I2cChip::Scd4x => write!(f, "SCD4x"),
+            I2cChip::Unknown => write!(f, "?"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct I2cDevice {
+    pub address: u8,
+    pub chip: I2cChip,
+}
+
+/// Devices answering on the I2C bus at startup
+pub struct I2cScan {
+    devices: ArrayVec<I2cDevice, MAX_I2C_DEVICES>,
+}
+
+impl I2cScan {
+    /// Address every device and identify the ones that answer
+    pub fn scan<I2C, E, D>(i2c: &mut I2C, delay: &mut D) -> Self
+    where
+        I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E> + i2c::Read<Error = E>,
+        D: DelayMs<u16>,
+    {
+        let mut devices = ArrayVec::new();
+
+        for address in FIRST_ADDRESS..=LAST_ADDRESS {
+            if devices.is_full() {
+                break;
+            }
+
+            // Empty write is acknowledged without changing the device state
+            if i2c.write(address, &[]).is_ok() {
+                let chip = identify(i2c, delay, address);
+                devices.push(I2cDevice { address, chip });
+            }
+        }
+
+        Self { devices }
+    }
+
+    pub fn devices(&self) -> &[I2cDevice] {
+        &self.devices
+    }
+
+    /// Address of the first BMP280/BME280 found
+    pub fn bmx280_address(&self) -> Option<u8> {
+        self.devices.iter()
+            .find(|device| matches!(device.chip, I2cChip::Bmx280(_)))
+            .map(|device| device.address)
+    }
+
+    /// Kind and address of every SHT3x/SHT4x sensor found
+    pub fn sht_sensors(&self) -> ArrayVec<(ShtKind, u8), MAX_SHT_SENSORS> {
+        self.devices.iter()
+            .filter_map(|device| match device.chip {
+                I2cChip::Sht(kind) => Some((kind, device.address)),
+                _ => None,
+            })
+            .take(MAX_SHT_SENSORS)
+            .collect()
+    }
+}
+
+fn identify<I2C, E, D>(i2c: &mut I2C, delay: &mut D, address: u8) -> I2cChip
+where
+    I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E> + i2c::Read<Error = E>,
+    D: DelayMs<u16>,
+{
+    match address {
+        // Neither has an ID register, the address is unique on this board
+        PCF8563_ADDRESS => I2cChip::Pcf8563,
+        SCD4X_ADDRESS => I2cChip::Scd4x,
+        ADDRESS_SDO_GROUNDED | ADDRESS_SDO_HIGH => {
+            let mut chip_id = [0];
+
+            i2c.write_read(address, &[BMX280_CHIP_ID_REGISTER], &mut chip_id).ok()
+                .and_then(|_| Bmx280Chip::from_chip_id(chip_id[0]))
+                .map_or(I2cChip::Unknown, I2cChip::Bmx280)
+        },
+        // SHT3x and SHT4x share the addresses 0x44 and 0x45, only the
+        // family whose command is understood returns a valid serial number
+        address if SHT_ADDRESSES.contains(&address) => [ShtKind::Sht4x, ShtKind::Sht3x].into_iter()
+            .find(|kind| Sht::new(i2c, delay, *kind, address).serial().is_some())
+            .map_or(I2cChip::Unknown, I2cChip::Sht),
+        _ => I2cChip::Unknown,
+    }
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+use embedded_hal::blocking::{i2c, delay::DelayMs};
+
+use super::sht::{read_word, sensirion_crc8};
+
+pub const ADDRESS: u8 = 0x62;
+
+const START_PERIODIC_MEASUREMENT: u16 = 0x21B1;
+const STOP_PERIODIC_MEASUREMENT: u16 = 0x3F86;
+const READ_MEASUREMENT: u16 = 0xEC05;
+const GET_DATA_READY_STATUS: u16 = 0xE4B8;
+const SET_AMBIENT_PRESSURE: u16 = 0xE000;
+const SET_AUTOMATIC_SELF_CALIBRATION: u16 = 0x2416;
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Co2Measurement {
+    /// In parts per million
+    pub co2: u16,
+    /// In 1/100 degrees celsius
+    pub temperature: i32,
+    /// In 1/100 percent of relative humidity
+    pub humidity: u16,
+}
+
+pub enum Scd4xError<E> {
+    Bus(E),
+    CrcMismatch,
+}
+
+impl<E> Scd4xError<E> {
+    /// Sensor not acknowledging its address was likely power cycled and
+    /// stopped the periodic measurement
+    fn is_reset(&self) -> bool {
+        matches!(self, Self::Bus(_))
+    }
+}
+
+/// Sensirion SCD40/SCD41 photoacoustic CO2 sensor in periodic
+/// measurement mode, a new value is available every 5 seconds
+pub struct Scd4x<I2C> {
+    i2c: I2C,
+    automatic_self_calibration: bool,
+    started: bool,
+    /// Ambient pressure last sent to the sensor in hPa
+    ambient_pressure: Option<u16>,
+    last_measurement: Option<Co2Measurement>,
+}
+
+impl<I2C, E> Scd4x<I2C>
+where I2C: i2c::Write<Error = E> + i2c::Read<Error = E> {
+    pub fn new(i2c: I2C, automatic_self_calibration: bool) -> Self {
+        Self {
+            i2c,
+            automatic_self_calibration,
+            started: false,
+            ambient_pressure: None,
+            last_measurement: None,
+        }
+    }
+
+    /// Return the latest measurement, `pressure` (in pascals) from another
+    /// sensor is used to compensate the CO2 reading
+    pub fn read<D: DelayMs<u16>>(
+        &mut self,
+        delay: &mut D,
+        pressure: Option<i32>,
+    ) -> Option<Co2Measurement> {
+        if !self.started {
+            self.started = self.start(delay).is_ok();
+            self.ambient_pressure = None;
+        }
+
+        if let Some(pressure) = pressure {
+            let hectopascals = (pressure/100).clamp(700, 1200) as u16;
+
+            // Sensor remembers the value, so it is sent only when it changes
+            if self.ambient_pressure != Some(hectopascals) {
+                match self.command_with_argument(SET_AMBIENT_PRESSURE, hectopascals) {
+                    Ok(()) => self.ambient_pressure = Some(hectopascals),
+                    Err(error) => self.started &= !error.is_reset(),
+                }
+
+                // Sensor does not acknowledge the next command before the execution time
+                delay.delay_ms(1);
+            }
+        }
+
+        match self.read_new_measurement(delay) {
+            Ok(Some(measurement)) => self.last_measurement = Some(measurement),
+            Ok(None) => {},
+            Err(error) if error.is_reset() => {
+                // Sensor may have been power cycled, start it again next time
+                self.started = false;
+                self.last_measurement = None;
+            },
+            // Corrupted transfer, the sensor keeps measuring
+            Err(_) => {},
+        }
+
+        self.last_measurement
+    }
+
+    /// Stop the measurement possibly running since before MCU reset,
+    /// configure automatic self calibration and start measuring
+    fn start<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), Scd4xError<E>> {
+        self.command(STOP_PERIODIC_MEASUREMENT)?;
+        delay.delay_ms(500);
+        let calibration = self.automatic_self_calibration as u16;
+        self.command_with_argument(SET_AUTOMATIC_SELF_CALIBRATION, calibration)?;
+        delay.delay_ms(1);
+        self.command(START_PERIODIC_MEASUREMENT)
+    }
+
+    fn read_new_measurement<D: DelayMs<u16>>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Option<Co2Measurement>, Scd4xError<E>> {
+        let [status] = self.read_words::<_, 1>(delay, GET_DATA_READY_STATUS)?;
+
+        if status & 0x07FF == 0 {
+            return Ok(None);
+        }
+
+        let [co2, temperature, humidity] = self.read_words::<_, 3>(delay, READ_MEASUREMENT)?;
+
+        Ok(Some(Co2Measurement {
+            co2,
+            temperature: -4500 + (17500*temperature as i32)/65535,
+            humidity: ((10000*humidity as u32)/65535) as u16,
+        }))
+    }
+
+    fn command(&mut self, command: u16) -> Result<(), Scd4xError<E>> {
+        self.i2c.write(ADDRESS, &command.to_be_bytes()).map_err(Scd4xError::Bus)
+    }
+
+    fn command_with_argument(&mut self, command: u16, argument: u16) -> Result<(), Scd4xError<E>> {
+        let [command_msb, command_lsb] = command.to_be_bytes();
+        let [argument_msb, argument_lsb] = argument.to_be_bytes();
+        let crc = sensirion_crc8(&[argument_msb, argument_lsb]);
+        self.i2c.write(ADDRESS, &[command_msb, command_lsb, argument_msb, argument_lsb, crc])
+            .map_err(Scd4xError::Bus)
+    }
+
+    /// Send the command and read `N` CRC protected words
+    fn read_words<D: DelayMs<u16>, const N: usize>(
+        &mut self,
+        delay: &mut D,
+        command: u16,
+    ) -> Result<[u16; N], Scd4xError<E>> {
+        let mut data = [0; 9];
+        let data = &mut data[..3*N];
+        self.command(command)?;
+        delay.delay_ms(1);
+        self.i2c.read(ADDRESS, data).map_err(Scd4xError::Bus)?;
+
+        let mut words = [0; N];
+
+        for (word, chunk) in words.iter_mut().zip(data.chunks(3)) {
+            *word = read_word(chunk).ok_or(Scd4xError::CrcMismatch)?;
+        }
+
+        Ok(words)
+    }
+}
@@ -0,0 +1,38 @@
+use core::cell::RefCell;
+use embedded_hal::blocking::i2c;
+
+/// Handle of a single driver to the I2C bus shared by all drivers, the bus
+/// is borrowed only for the duration of a transaction
+pub struct I2cProxy<'a, I2C> {
+    bus: &'a RefCell<I2C>,
+}
+
+impl<'a, I2C> I2cProxy<'a, I2C> {
+    pub fn new(bus: &'a RefCell<I2C>) -> Self {
+        Self { bus }
+    }
+}
+
+impl<I2C: i2c::Write> i2c::Write for I2cProxy<'_, I2C> {
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().write(address, bytes)
+    }
+}
+
+impl<I2C: i2c::Read> i2c::Read for I2cProxy<'_, I2C> {
+    type Error = I2C::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().read(address, buffer)
+    }
+}
+
+impl<I2C: i2c::WriteRead> i2c::WriteRead for I2cProxy<'_, I2C> {
+    type Error = I2C::Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().write_read(address, bytes, buffer)
+    }
+}
\ No newline at end of file
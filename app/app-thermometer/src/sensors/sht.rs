@@ -0,0 +1,195 @@
+use core::fmt::Display;
+use embedded_hal::blocking::{i2c, delay::DelayMs};
+
+use crate::config::MAX_SHT_SENSORS;
+
+pub const ADDRESS_DEFAULT: u8 = 0x44;
+
+/// SHT3x answers at 0x44 or 0x45, the SHT4x variants at 0x44 - 0x46,
+/// every address has its own calibration and filter slot
+pub const ADDRESSES: core::ops::Range<u8> = ADDRESS_DEFAULT..ADDRESS_DEFAULT + MAX_SHT_SENSORS as u8;
+
+/// Sensirion humidity sensor families, they share the data format
+/// and CRC but use different commands
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum ShtKind {
+    /// SHT30, SHT31, SHT35
+    Sht3x,
+    /// SHT40, SHT41, SHT45
+    Sht4x,
+}
+
+impl ShtKind {
+    fn measure_command(&self) -> &'static [u8] {
+        match self {
+            // Single shot, high repeatability, no clock stretching
+            ShtKind::Sht3x => &[0x24, 0x00],
+            // Single shot, high precision
+            ShtKind::Sht4x => &[0xFD],
+        }
+    }
+
+    fn measure_time_ms(&self) -> u16 {
+        match self {
+            ShtKind::Sht3x => 16,
+            ShtKind::Sht4x => 10,
+        }
+    }
+
+    fn serial_command(&self) -> &'static [u8] {
+        match self {
+            ShtKind::Sht3x => &[0x37, 0x80],
+            ShtKind::Sht4x => &[0x89],
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct ShtMeasurement {
+    /// In 1/100 degrees celsius
+    pub temperature: i32,
+    /// In 1/100 percent of relative humidity
+    pub humidity: u16,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum ShtError {
+    /// Command was not acknowledged, the sensor is missing or unpowered
+    NoResponse,
+    /// Reading the result failed after the command was acknowledged
+    Bus,
+    CrcMismatch,
+}
+
+impl Display for ShtError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ShtError::NoResponse => write!(f, "NR"),
+            ShtError::Bus => write!(f, "BUS"),
+            ShtError::CrcMismatch => write!(f, "CRC"),
+        }
+    }
+}
+
+pub struct Sht {
+    kind: ShtKind,
+    address: u8,
+    serial: Option<u32>,
+}
+
+impl Sht {
+    /// Read the serial number, sensor is registered even if it does
+    /// not answer so that it is reported as missing
+    pub fn new<I2C, E, D>(i2c: &mut I2C, delay: &mut D, kind: ShtKind, address: u8) -> Self
+    where I2C: i2c::Write<Error = E> + i2c::Read<Error = E>, D: DelayMs<u16> {
+        let mut sensor = Self { kind, address, serial: None };
+        sensor.serial = sensor.read_serial(i2c, delay).ok();
+        sensor
+    }
+
+    pub fn kind(&self) -> ShtKind {
+        self.kind
+    }
+
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Factory serial number for traceability, `None` if it could not be read
+    pub fn serial(&self) -> Option<u32> {
+        self.serial
+    }
+
+    pub fn measure<I2C, E, D>(
+        &mut self,
+        i2c: &mut I2C,
+        delay: &mut D
+    ) -> Result<ShtMeasurement, ShtError>
+    where I2C: i2c::Write<Error = E> + i2c::Read<Error = E>, D: DelayMs<u16> {
+        if self.serial.is_none() {
+            // Sensor was not present at startup or has been replaced
+            self.serial = self.read_serial(i2c, delay).ok();
+        }
+
+        let [raw_temperature, raw_humidity] = self.command(
+            i2c, delay, self.kind.measure_command(), self.kind.measure_time_ms()
+        )?;
+
+        Ok(self.convert(raw_temperature, raw_humidity))
+    }
+
+    /// Heat the sensor shortly to evaporate condensed water, measurements
+    /// taken shortly after heating read too warm and too dry
+    pub fn heat<I2C, E, D>(&mut self, i2c: &mut I2C, delay: &mut D) -> Result<(), ShtError>
+    where I2C: i2c::Write<Error = E> + i2c::Read<Error = E>, D: DelayMs<u16> {
+        match self.kind {
+            ShtKind::Sht3x => {
+                i2c.write(self.address, &[0x30, 0x6D]).map_err(|_| ShtError::NoResponse)?;
+                delay.delay_ms(100);
+                i2c.write(self.address, &[0x30, 0x66]).map_err(|_| ShtError::NoResponse)
+            },
+            ShtKind::Sht4x => {
+                // 200 mW for 0.1 s, followed by a measurement that is thrown away
+                self.command(i2c, delay, &[0x32], 110).map(|_| ())
+            },
+        }
+    }
+
+    fn read_serial<I2C, E, D>(&mut self, i2c: &mut I2C, delay: &mut D) -> Result<u32, ShtError>
+    where I2C: i2c::Write<Error = E> + i2c::Read<Error = E>, D: DelayMs<u16> {
+        let [high, low] = self.command(i2c, delay, self.kind.serial_command(), 1)?;
+        Ok(((high as u32) << 16) | low as u32)
+    }
+
+    /// Send the command and read two CRC protected words after `wait_ms`
+    fn command<I2C, E, D>(
+        &mut self,
+        i2c: &mut I2C,
+        delay: &mut D,
+        command: &[u8],
+        wait_ms: u16,
+    ) -> Result<[u16; 2], ShtError>
+    where I2C: i2c::Write<Error = E> + i2c::Read<Error = E>, D: DelayMs<u16> {
+        let mut data = [0; 6];
+        i2c.write(self.address, command).map_err(|_| ShtError::NoResponse)?;
+        delay.delay_ms(wait_ms);
+        i2c.read(self.address, &mut data).map_err(|_| ShtError::Bus)?;
+
+        match (read_word(&data[0..3]), read_word(&data[3..6])) {
+            (Some(first), Some(second)) => Ok([first, second]),
+            _ => Err(ShtError::CrcMismatch),
+        }
+    }
+
+    fn convert(&self, raw_temperature: u16, raw_humidity: u16) -> ShtMeasurement {
+        let temperature = -4500 + (17500*raw_temperature as i32)/65535;
+
+        let humidity = match self.kind {
+            ShtKind::Sht3x => (10000*raw_humidity as i32)/65535,
+            ShtKind::Sht4x => (-600 + (12500*raw_humidity as i32)/65535).clamp(0, 10000),
+        };
+
+        ShtMeasurement { temperature, humidity: humidity as u16 }
+    }
+}
+
+/// Big endian word followed by its CRC, `None` if the CRC does not match
+pub fn read_word(data: &[u8]) -> Option<u16> {
+    match sensirion_crc8(&data[0..2]) == data[2] {
+        true => Some(u16::from_be_bytes([data[0], data[1]])),
+        false => None,
+    }
+}
+
+/// CRC-8 with polynomial 0x31 and initial value 0xFF used by Sensirion sensors
+pub fn sensirion_crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0xFF, |crc, byte| {
+        let mut crc = crc ^ byte;
+
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x31 } else { crc << 1 };
+        }
+
+        crc
+    })
+}
@@ -0,0 +1,123 @@
+use core::cell::RefCell;
+use stm32f4xx_hal::{
+    adc::{Adc, Vref, Temperature}, pac::ADC1, signature::{VrefCal, VtempCal30, VtempCal110}
+};
+
+use super::analog::{AnalogInput, oversample};
+
+/// Highest value of a single 12-bit conversion
+const FULL_SCALE: u32 = 4095;
+
+/// Supply voltage at which the factory VREFINT and temperature sensor
+/// calibrations were taken
+const VREFINT_CAL_MILLIVOLTS: u32 = 3300;
+
+/// Resistors of the divider between the battery and the ADC pin
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct VoltageDivider {
+    /// Between the battery and the pin
+    pub top_ohms: u32,
+    /// Between the pin and ground
+    pub bottom_ohms: u32,
+}
+
+pub struct SupplyConfig {
+    /// Battery is measured through the divider when configured, otherwise
+    /// the supply voltage itself is monitored (battery without regulator)
+    pub battery_divider: Option<VoltageDivider>,
+    /// Monitored voltage in millivolts reported as 0 percent
+    pub empty_millivolts: u16,
+    /// Monitored voltage in millivolts reported as 100 percent
+    pub full_millivolts: u16,
+    /// Number of consecutive readings below `empty_millivolts` before the
+    /// battery is reported low, SD card writes cause short voltage drops
+    pub low_readings: u32,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Default)]
+pub struct SupplyVoltage {
+    /// Analog supply voltage in millivolts measured against VREFINT
+    pub supply: u16,
+    /// Battery voltage in millivolts, if the battery divider is configured
+    pub battery: Option<u16>,
+    /// Charge estimate interpolated between the empty and full voltages
+    pub percentage: u8,
+    /// Monitored voltage stayed below the empty voltage, time to shut down
+    pub low: bool,
+}
+
+/// Supply and battery voltage measured by the ADC shared with the
+/// analog probes
+pub struct SupplyMonitor<'a> {
+    adc: &'a RefCell<Adc<ADC1>>,
+    battery_input: &'a dyn AnalogInput,
+    config: SupplyConfig,
+    oversampling: u32,
+    readings_below_empty: u32,
+}
+
+impl<'a> SupplyMonitor<'a> {
+    /// `battery_input` is sampled only when the battery divider is configured
+    pub fn new(
+        adc: &'a RefCell<Adc<ADC1>>,
+        battery_input: &'a dyn AnalogInput,
+        config: SupplyConfig,
+        oversampling: u32,
+    ) -> Self {
+        adc.borrow_mut().enable_temperature_and_vref();
+
+        Self {
+            adc,
+            battery_input,
+            config,
+            oversampling: oversampling.max(1),
+            readings_below_empty: 0,
+        }
+    }
+
+    pub fn read(&mut self) -> SupplyVoltage {
+        let mut adc = self.adc.borrow_mut();
+        let vref = oversample(&mut adc, &Vref, self.oversampling).max(1);
+        let calibration = VrefCal::get().read() as u32;
+        let supply = VREFINT_CAL_MILLIVOLTS*calibration*self.oversampling/vref;
+
+        let battery = self.config.battery_divider.map(|divider| {
+            let sample = oversample(&mut adc, self.battery_input, self.oversampling) as u64;
+            let pin_millivolts = sample*supply as u64/(FULL_SCALE*self.oversampling) as u64;
+            let total_ohms = (divider.top_ohms + divider.bottom_ohms) as u64;
+            (pin_millivolts*total_ohms/divider.bottom_ohms.max(1) as u64) as u16
+        });
+
+        let monitored = battery.unwrap_or(supply as u16);
+
+        self.readings_below_empty = match monitored < self.config.empty_millivolts {
+            true => self.readings_below_empty.saturating_add(1),
+            false => 0,
+        };
+
+        SupplyVoltage {
+            supply: supply as u16,
+            battery,
+            percentage: percentage(monitored, &self.config),
+            low: self.readings_below_empty >= self.config.low_readings.max(1),
+        }
+    }
+
+    /// MCU die temperature in 1/100 degrees celsius from the internal sensor
+    /// calibrated at 30 and 110 degrees, `supply` in millivolts compensates
+    /// the conversion for the actual reference voltage
+    pub fn read_die_temperature(&mut self, supply: u16) -> i32 {
+        let sample = oversample(&mut self.adc.borrow_mut(), &Temperature, self.oversampling) as i64;
+        let calibrated = sample*supply as i64/(VREFINT_CAL_MILLIVOLTS*self.oversampling) as i64;
+        let cal30 = VtempCal30::get().read() as i64;
+        let cal110 = VtempCal110::get().read() as i64;
+        (3000 + (calibrated - cal30)*8000/(cal110 - cal30).max(1)) as i32
+    }
+}
+
+fn percentage(millivolts: u16, config: &SupplyConfig) -> u8 {
+    let empty = config.empty_millivolts as u32;
+    let full = (config.full_millivolts as u32).max(empty + 1);
+    let clamped = (millivolts as u32).clamp(empty, full);
+    ((clamped - empty)*100/(full - empty)) as u8
+}
\ No newline at end of file
@@ -0,0 +1,43 @@
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use embedded_hal::blocking::{delay::{DelayUs, DelayMs}};
+
+use super::dht::{Dht, DhtKind, Measurement, DhtError};
+
+/// Configuration of a single DHT sensor
+#[derive(Copy, Clone)]
+pub struct DhtChannel {
+    pub kind: DhtKind,
+    /// Mold index is tracked for the room the sensor is in
+    pub mold: bool,
+}
+
+/// Collection of `N` DHT sensors sharing the same (type erased) pin type,
+/// every sensor can be of a different kind
+pub struct DhtDrivers<T, const N: usize> {
+    sensors: [Dht<T>; N],
+    cycles_per_us: u32,
+}
+
+impl<T, const N: usize> DhtDrivers<T, N>
+where T: InputPin + OutputPin {
+    /// `cycles_per_us` is the DWT cycle counter (core clock) frequency in MHz
+    pub fn new(sensors: [Dht<T>; N], cycles_per_us: u32) -> Self {
+        Self { sensors, cycles_per_us }
+    }
+}
+
+pub trait DhtReader<D, const N: usize>
+where D: DelayUs<u16> + DelayMs<u16>{
+    fn read(&mut self, delay: &mut D) -> [Result<Measurement, DhtError>; N];
+}
+
+impl<T, D, const N: usize> DhtReader<D, N> for DhtDrivers<T, N>
+where
+T: InputPin + OutputPin,
+D: DelayUs<u16> + DelayMs<u16> {
+    fn read(&mut self, delay: &mut D) -> [Result<Measurement, DhtError>; N] {
+        let sensors = &mut self.sensors;
+        let cycles_per_us = self.cycles_per_us;
+        core::array::from_fn(|index| sensors[index].perform_measurement(delay, cycles_per_us))
+    }
+}
@@ -0,0 +1,70 @@
+use core::fmt::Debug;
+use arrayvec::ArrayVec;
+use lib_onewire::{
+    OneWire, OneWireBus, OneWireError, DeviceSearch, Address, ds18b20::{self, Resolution}
+};
+
+use crate::config::{MAX_DS18B20_PROBES, MAX_TEMPERATURE_PROBES};
+use super::{TemperatureProbe, ProbeSource, ProbeReader, ProbeError};
+
+/// DS18B20 probes discovered on a single 1-Wire bus
+pub struct Ds18b20Probes<B> {
+    bus: OneWire<B>,
+    addresses: ArrayVec<Address, MAX_DS18B20_PROBES>,
+    resolution: Resolution,
+}
+
+impl<B, E> Ds18b20Probes<B>
+where B: OneWireBus<Error = E>, E: Debug {
+    /// Enumerate DS18B20 probes on the bus, set their resolution
+    /// and start the first conversion
+    pub fn new(bus: B, resolution: Resolution) -> Self {
+        let mut bus = OneWire::new(bus);
+        let mut addresses = ArrayVec::new();
+        let mut search = DeviceSearch::default();
+
+        while let Ok(Some(address)) = search.next(&mut bus) {
+            if address.family_code() == ds18b20::FAMILY_CODE && !addresses.is_full() {
+                addresses.push(address);
+            }
+        }
+
+        for address in addresses.iter() {
+            let _ = ds18b20::set_resolution(&mut bus, Some(address), resolution);
+        }
+
+        let _ = ds18b20::start_conversion(&mut bus, None);
+
+        Self { bus, addresses, resolution }
+    }
+}
+
+impl<B, E> ProbeReader for Ds18b20Probes<B>
+where B: OneWireBus<Error = E>, E: Debug {
+    /// Read results of the previous conversion and start the next one,
+    /// so the measurement loop never waits for the conversion to finish
+    fn read(&mut self, probes: &mut ArrayVec<TemperatureProbe, MAX_TEMPERATURE_PROBES>) {
+        for address in self.addresses.iter() {
+            let temperature = ds18b20::read_temperature(
+                &mut self.bus, address, self.resolution
+            ).map_err(to_probe_error);
+
+            let _ = probes.try_push(TemperatureProbe {
+                source: ProbeSource::Ds18b20(*address),
+                temperature,
+            });
+        }
+
+        let _ = ds18b20::start_conversion(&mut self.bus, None);
+    }
+}
+
+fn to_probe_error<E>(error: OneWireError<E>) -> ProbeError
+where E: Debug {
+    match error {
+        OneWireError::NoPresence => ProbeError::NotResponding,
+        OneWireError::CrcMismatch => ProbeError::CrcMismatch,
+        OneWireError::NotConverted => ProbeError::NotConverted,
+        OneWireError::Bus(_) => ProbeError::Bus,
+    }
+}
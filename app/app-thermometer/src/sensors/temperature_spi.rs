@@ -0,0 +1,34 @@
+use arrayvec::ArrayVec;
+use embedded_hal::{blocking::spi, digital::v2::OutputPin};
+
+use crate::config::MAX_TEMPERATURE_PROBES;
+use super::{TemperatureProbe, ProbeSource, ProbeReader, Max31855, Max31865};
+
+/// Thermocouple and RTD converters sharing a single SPI bus in mode 1
+pub struct SpiProbes<SPI, TC, RTD> {
+    spi: SPI,
+    thermocouple: Max31855<TC>,
+    rtd: Max31865<RTD>,
+}
+
+impl<SPI, TC, RTD, E> SpiProbes<SPI, TC, RTD>
+where SPI: spi::Transfer<u8, Error = E>, TC: OutputPin, RTD: OutputPin {
+    pub fn new(spi: SPI, thermocouple: Max31855<TC>, rtd: Max31865<RTD>) -> Self {
+        Self { spi, thermocouple, rtd }
+    }
+}
+
+impl<SPI, TC, RTD, E> ProbeReader for SpiProbes<SPI, TC, RTD>
+where SPI: spi::Transfer<u8, Error = E>, TC: OutputPin, RTD: OutputPin {
+    fn read(&mut self, probes: &mut ArrayVec<TemperatureProbe, MAX_TEMPERATURE_PROBES>) {
+        let _ = probes.try_push(TemperatureProbe {
+            source: ProbeSource::Thermocouple,
+            temperature: self.thermocouple.read(&mut self.spi),
+        });
+
+        let _ = probes.try_push(TemperatureProbe {
+            source: ProbeSource::Rtd,
+            temperature: self.rtd.read(&mut self.spi),
+        });
+    }
+}
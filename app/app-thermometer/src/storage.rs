@@ -0,0 +1,34 @@
+use stm32f4xx_hal::{pac::FLASH, flash::FlashExt};
+
+use crate::calibration::Calibrations;
+
+/// Last 128 KiB sector of the STM32F411 flash, it is left out of the program
+/// memory in memory.x
+const CALIBRATION_SECTOR: u8 = 7;
+const CALIBRATION_OFFSET: usize = 0x60000;
+
+/// Calibration kept in the internal flash, it survives power loss
+/// and firmware updates
+pub struct CalibrationStorage {
+    flash: FLASH,
+}
+
+impl CalibrationStorage {
+    pub fn new(flash: FLASH) -> Self {
+        Self { flash }
+    }
+
+    /// Stored calibration, every channel is uncalibrated when there is none
+    pub fn load(&self) -> Calibrations {
+        Calibrations::decode(&self.flash.read()[CALIBRATION_OFFSET..]).unwrap_or_default()
+    }
+
+    /// Erase the sector and write the calibration, takes up to a few seconds
+    pub fn store(&mut self, calibrations: &Calibrations) -> Result<(), ()> {
+        let mut data = [0; Calibrations::ENCODED_LENGTH];
+        let length = calibrations.encode(&mut data).ok_or(())?;
+        let mut flash = self.flash.unlocked();
+        flash.erase(CALIBRATION_SECTOR).map_err(|_| ())?;
+        flash.program(CALIBRATION_OFFSET, data[..length].iter()).map_err(|_| ())
+    }
+}
\ No newline at end of file
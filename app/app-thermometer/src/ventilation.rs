@@ -0,0 +1,153 @@
+use embedded_hal::digital::v2::OutputPin;
+use lib_weather::Psychrometrics;
+
+use crate::sensors::{Measurement, DhtError};
+use stm32f4xx_hal::gpio::{ErasedPin, Output, PushPull};
+
+/// Indoor and outdoor DHT sensor pair and the switching thresholds
+pub struct VentilationConfig {
+    /// Index of the DHT sensor in the ventilated room
+    pub indoor: usize,
+    /// Index of the DHT sensor outside
+    pub outdoor: usize,
+    /// Absolute humidity difference (indoor minus outdoor) in 1/100 g/m3
+    /// above which ventilation dries the room
+    pub start_difference: i32,
+    /// Absolute humidity difference in 1/100 g/m3 below which ventilation
+    /// stops, lower than `start_difference` to avoid flapping
+    pub stop_difference: i32,
+    /// Outdoor dew point has to stay this much (in 1/100 degrees celsius)
+    /// below the indoor temperature, otherwise the air condenses inside
+    pub condensation_margin: i32,
+    pub minimum_on_minutes: u32,
+    pub minimum_off_minutes: u32,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Advice {
+    Ventilate,
+    KeepClosed,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Ventilation {
+    pub advice: Advice,
+    /// Indoor minus outdoor absolute humidity in 1/100 g/m3
+    pub humidity_difference: i32,
+    /// Indoor minus outdoor dew point in 1/100 degrees celsius
+    pub dew_point_difference: i32,
+    /// State of the fan relay, if connected
+    pub fan: Option<bool>,
+}
+
+/// Decides whether opening the window dries the room, the optional fan
+/// relay follows the advice once the minimum on/off time has passed
+pub struct VentilationAdvisor {
+    config: VentilationConfig,
+    relay: Option<ErasedPin<Output<PushPull>>>,
+    advice: Advice,
+    fan_on: bool,
+    /// Time of the last fan switching in minutes
+    fan_switched: Option<u32>,
+}
+
+impl VentilationAdvisor {
+    pub fn new(config: VentilationConfig, relay: Option<ErasedPin<Output<PushPull>>>) -> Self {
+        let mut advisor = Self {
+            config,
+            relay,
+            advice: Advice::KeepClosed,
+            fan_on: false,
+            fan_switched: None,
+        };
+
+        advisor.set_fan(false);
+        advisor
+    }
+
+    /// Update the advice from the DHT measurements and the values derived
+    /// from them, the advice is to keep closed while any of the paired values
+    /// is missing, `time` is a monotonic minute counter for the minimum fan
+    /// on/off times
+    pub fn update(
+        &mut self,
+        measurements: &[Result<Measurement, DhtError>],
+        derived: &[Option<Psychrometrics>],
+        time: Option<u32>,
+    ) -> Option<Ventilation> {
+        let indoor = derived.get(self.config.indoor).copied().flatten();
+        let outdoor = derived.get(self.config.outdoor).copied().flatten();
+        let indoor_temperature = measurements.get(self.config.indoor).copied().and_then(Result::ok)
+            .map(|values| values.temperature as i32*10);
+
+        let values = match (indoor, outdoor, indoor_temperature) {
+            (Some(indoor), Some(outdoor), Some(temperature)) => Some((indoor, outdoor, temperature)),
+            _ => None,
+        };
+
+        let ventilation = values.map(|(indoor, outdoor, temperature)| {
+            let humidity_difference = indoor.absolute_humidity as i32 - outdoor.absolute_humidity as i32;
+            let dry = outdoor.dew_point < temperature - self.config.condensation_margin;
+
+            self.advice = match self.advice {
+                Advice::KeepClosed if humidity_difference >= self.config.start_difference && dry =>
+                    Advice::Ventilate,
+                Advice::Ventilate if humidity_difference < self.config.stop_difference || !dry =>
+                    Advice::KeepClosed,
+                advice => advice,
+            };
+
+            (humidity_difference, indoor.dew_point - outdoor.dew_point)
+        });
+
+        if ventilation.is_none() {
+            self.advice = Advice::KeepClosed;
+        }
+
+        self.switch_fan(self.advice == Advice::Ventilate, time);
+
+        let fan = self.relay.as_ref().map(|_| self.fan_on);
+
+        ventilation.map(|(humidity_difference, dew_point_difference)| Ventilation {
+            advice: self.advice,
+            humidity_difference,
+            dew_point_difference,
+            fan,
+        })
+    }
+
+    /// Switch the fan when it was in the current state long enough, without
+    /// a known time the fan can be only switched off
+    fn switch_fan(&mut self, on: bool, time: Option<u32>) {
+        if on == self.fan_on {
+            return;
+        }
+
+        let minimum = match self.fan_on {
+            true => self.config.minimum_on_minutes,
+            false => self.config.minimum_off_minutes,
+        };
+
+        let allowed = match (time, self.fan_switched) {
+            (Some(time), Some(switched)) => time < switched || time - switched >= minimum,
+            (Some(_), None) => true,
+            (None, _) => !on,
+        };
+
+        if allowed {
+            self.set_fan(on);
+            self.fan_switched = time;
+        }
+    }
+
+    fn set_fan(&mut self, on: bool) {
+        self.fan_on = on;
+
+        if let Some(relay) = self.relay.as_mut() {
+            let _ = match on {
+                true => relay.set_high(),
+                false => relay.set_low(),
+            };
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,232 @@
+use core::fmt::Debug;
+use embedded_hal::blocking::i2c;
+
+use crate::chip::Bmx280Chip;
+use crate::error::Bmx280Error;
+use crate::registers::{REGISTER_CALIBRATION_TP, REGISTER_CALIBRATION_H1, REGISTER_CALIBRATION_H2, read_register};
+
+/// Factory calibration, compensation formulas are the integer
+/// versions from the Bosch datasheets
+pub struct Calibration {
+    t1: u16,
+    t2: i16,
+    t3: i16,
+    p1: u16,
+    p2: i16,
+    p3: i16,
+    p4: i16,
+    p5: i16,
+    p6: i16,
+    p7: i16,
+    p8: i16,
+    p9: i16,
+    pub humidity: Option<HumidityCalibration>,
+}
+
+pub struct HumidityCalibration {
+    h1: u8,
+    h2: i16,
+    h3: u8,
+    h4: i16,
+    h5: i16,
+    h6: i8,
+}
+
+impl Calibration {
+    pub fn read<I2C, E>(
+        i2c: &mut I2C,
+        address: u8,
+        chip: Bmx280Chip,
+    ) -> Result<Self, Bmx280Error<E>>
+    where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug {
+        let mut data = [0; 24];
+
+        i2c.write_read(address, &[REGISTER_CALIBRATION_TP], &mut data)
+            .map_err(Bmx280Error::Bus)?;
+
+        let unsigned = |index: usize| u16::from_le_bytes([data[index], data[index + 1]]);
+        let signed = |index: usize| i16::from_le_bytes([data[index], data[index + 1]]);
+
+        let humidity = match chip {
+            Bmx280Chip::Bmp280 => None,
+            Bmx280Chip::Bme280 => Some(HumidityCalibration::read(i2c, address)?),
+        };
+
+        Ok(Self {
+            t1: unsigned(0),
+            t2: signed(2),
+            t3: signed(4),
+            p1: unsigned(6),
+            p2: signed(8),
+            p3: signed(10),
+            p4: signed(12),
+            p5: signed(14),
+            p6: signed(16),
+            p7: signed(18),
+            p8: signed(20),
+            p9: signed(22),
+            humidity,
+        })
+    }
+
+    /// Fine temperature in 1/5120 degrees, input of the other compensations
+    pub fn t_fine(&self, raw: i32) -> i32 {
+        let t1 = self.t1 as i32;
+        let var1 = (((raw >> 3) - (t1 << 1))*self.t2 as i32) >> 11;
+        let var2 = (((((raw >> 4) - t1)*((raw >> 4) - t1)) >> 12)*self.t3 as i32) >> 14;
+        var1 + var2
+    }
+
+    /// Pressure in pascals
+    pub fn compensate_pressure(&self, raw: i32, t_fine: i32) -> i32 {
+        let mut var1 = t_fine as i64 - 128000;
+        let mut var2 = var1*var1*self.p6 as i64;
+        var2 += (var1*self.p5 as i64) << 17;
+        var2 += (self.p4 as i64) << 35;
+        var1 = ((var1*var1*self.p3 as i64) >> 8) + ((var1*self.p2 as i64) << 12);
+        var1 = (((1_i64 << 47) + var1)*self.p1 as i64) >> 33;
+
+        if var1 == 0 {
+            // Avoid division by zero with invalid calibration
+            return 0;
+        }
+
+        let mut pressure = 1048576 - raw as i64;
+        pressure = (((pressure << 31) - var2)*3125)/var1;
+        var1 = (self.p9 as i64*(pressure >> 13)*(pressure >> 13)) >> 25;
+        var2 = (self.p8 as i64*pressure) >> 19;
+        pressure = ((pressure + var1 + var2) >> 8) + ((self.p7 as i64) << 4);
+
+        // Result is in 1/256 pascals
+        (pressure >> 8) as i32
+    }
+}
+
+impl HumidityCalibration {
+    pub fn read<I2C, E>(i2c: &mut I2C, address: u8) -> Result<Self, Bmx280Error<E>>
+    where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug {
+        let h1 = read_register(i2c, address, REGISTER_CALIBRATION_H1)?;
+        let mut data = [0; 7];
+
+        i2c.write_read(address, &[REGISTER_CALIBRATION_H2], &mut data)
+            .map_err(Bmx280Error::Bus)?;
+
+        Ok(Self {
+            h1,
+            h2: i16::from_le_bytes([data[0], data[1]]),
+            h3: data[2],
+            // H4 and H5 are 12-bit values sharing the nibbles of a single byte
+            h4: ((data[3] as i8 as i16) << 4) | (data[4] & 0x0F) as i16,
+            h5: ((data[5] as i8 as i16) << 4) | (data[4] >> 4) as i16,
+            h6: data[6] as i8,
+        })
+    }
+
+    /// Relative humidity in 1/10 percent
+    pub fn compensate(&self, raw: i32, t_fine: i32) -> u16 {
+        let x = t_fine - 76800;
+
+        let mut value = ((raw << 14) - ((self.h4 as i32) << 20) - (self.h5 as i32*x) + 16384) >> 15;
+
+        value *= ((((((x*self.h6 as i32) >> 10)*(((x*self.h3 as i32) >> 11) + 32768)) >> 10)
+            + 2097152)*self.h2 as i32 + 8192) >> 14;
+
+        value -= ((((value >> 15)*(value >> 15)) >> 7)*self.h1 as i32) >> 4;
+        let value = value.clamp(0, 419430400);
+
+        // Result is in 1/1024 percent
+        ((value >> 12)*10/1024) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calibration of the compensation example in the BMP280 datasheet
+    fn datasheet_calibration(humidity: Option<HumidityCalibration>) -> Calibration {
+        Calibration {
+            t1: 27504,
+            t2: 26435,
+            t3: -1000,
+            p1: 36477,
+            p2: -10685,
+            p3: 3024,
+            p4: 2855,
+            p5: 140,
+            p6: -7,
+            p7: 15500,
+            p8: -14600,
+            p9: 6000,
+            humidity,
+        }
+    }
+
+    /// Humidity calibration read from a BME280
+    fn humidity_calibration() -> HumidityCalibration {
+        HumidityCalibration { h1: 75, h2: 362, h3: 0, h4: 313, h5: 50, h6: 30 }
+    }
+
+    /// Floating point humidity compensation of the BME280 datasheet in percent
+    fn humidity_reference(calibration: &HumidityCalibration, raw: i32, t_fine: i32) -> f64 {
+        let x = t_fine as f64 - 76800.0;
+        let h = (raw as f64 - (calibration.h4 as f64*64.0 + calibration.h5 as f64/16384.0*x))
+            *(calibration.h2 as f64/65536.0*(1.0 + calibration.h6 as f64/67108864.0*x
+            *(1.0 + calibration.h3 as f64/67108864.0*x)));
+        (h*(1.0 - calibration.h1 as f64*h/524288.0)).clamp(0.0, 100.0)
+    }
+
+    #[test]
+    fn datasheet_example() {
+        let calibration = datasheet_calibration(None);
+        let t_fine = calibration.t_fine(519888);
+
+        assert_eq!(t_fine, 128422);
+        // 25.08 degrees celsius
+        assert_eq!((t_fine*5 + 128) >> 8, 2508);
+        // 100653.27 pascals in the datasheet
+        assert_eq!(calibration.compensate_pressure(415148, t_fine), 100653);
+    }
+
+    #[test]
+    fn pressure_follows_raw_value() {
+        let calibration = datasheet_calibration(None);
+        let t_fine = calibration.t_fine(519888);
+
+        // Raw pressure value decreases with rising pressure
+        assert!(calibration.compensate_pressure(400000, t_fine) > 100653);
+        assert!(calibration.compensate_pressure(430000, t_fine) < 100653);
+    }
+
+    #[test]
+    fn invalid_pressure_calibration() {
+        let mut calibration = datasheet_calibration(None);
+        calibration.p1 = 0;
+
+        assert_eq!(calibration.compensate_pressure(415148, 128422), 0);
+    }
+
+    #[test]
+    fn bme280_humidity() {
+        let calibration = datasheet_calibration(Some(humidity_calibration()));
+        let humidity = calibration.humidity.as_ref().unwrap();
+
+        for raw_temperature in [400000, 519888, 600000] {
+            let t_fine = calibration.t_fine(raw_temperature);
+
+            for raw in [25000, 28000, 30000, 32000, 35000] {
+                let expected = humidity_reference(humidity, raw, t_fine);
+                let compensated = humidity.compensate(raw, t_fine) as f64/10.0;
+
+                assert!(
+                    (compensated - expected).abs() <= 0.15,
+                    "raw {} gives {} instead of {}", raw, compensated, expected
+                );
+            }
+        }
+
+        // Limited to the range of relative humidity
+        assert_eq!(humidity.compensate(0, 128422), 0);
+        assert_eq!(humidity.compensate(65535, 128422), 1000);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+pub const ADDRESS_SDO_GROUNDED: u8 = 0x76;
+pub const ADDRESS_SDO_HIGH: u8 = 0x77;
+
+/// Pin compatible Bosch sensors, BME280 measures humidity in addition
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Bmx280Chip {
+    Bmp280,
+    Bme280,
+}
+
+impl Bmx280Chip {
+    pub fn from_chip_id(chip_id: u8) -> Option<Self> {
+        match chip_id {
+            0x56..=0x58 => Some(Bmx280Chip::Bmp280),
+            0x60 => Some(Bmx280Chip::Bme280),
+            _ => None,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,181 @@
+use core::fmt::Debug;
+use embedded_hal::blocking::{i2c, delay::DelayMs};
+
+use crate::calibration::Calibration;
+use crate::chip::Bmx280Chip;
+use crate::error::Bmx280Error;
+use crate::profile::{Bmx280Profile, Bmx280Mode};
+use crate::registers::{
+    REGISTER_CHIP_ID, REGISTER_CTRL_HUM, REGISTER_CTRL_MEAS, REGISTER_CONFIG,
+    read_register, write_register
+};
+
+/// Offsets of the registers in a burst read starting at the ctrl_hum register
+const CTRL_HUM_OFFSET: usize = 0;
+const CTRL_MEAS_OFFSET: usize = 2;
+const CONFIG_OFFSET: usize = 3;
+const DATA_OFFSET: usize = 5;
+
+/// Value of the data registers when no measurement has finished yet
+const SKIPPED_MEASUREMENT: i32 = 0x80000;
+const SKIPPED_HUMIDITY: i32 = 0x8000;
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Measurement {
+    /// In 1/100 degrees celsius
+    pub temperature: i32,
+    /// In pascals
+    pub pressure: i32,
+    /// In 1/10 percent of relative humidity, BME280 only
+    pub humidity: Option<u16>,
+}
+
+/// Driver for BMP280 and BME280 kept alive across readings, the chip is
+/// detected using the chip ID register and its calibration is cached, the
+/// chip is initialized again after a bus error or when it was reset
+pub struct Bmx280 {
+    address: u8,
+    profile: Bmx280Profile,
+    device: Option<Device>,
+}
+
+/// Detected chip with its calibration
+struct Device {
+    chip: Bmx280Chip,
+    calibration: Calibration,
+}
+
+impl Bmx280 {
+    /// Chip is initialized with the first reading
+    pub fn new(address: u8, profile: Bmx280Profile) -> Self {
+        Self { address, profile, device: None }
+    }
+
+    /// Measure in the forced mode or read the last finished measurement in
+    /// the normal mode, initialization is retried once after a failure
+    pub fn read<I2C, E, D>(
+        &mut self,
+        i2c: &mut I2C,
+        delay: &mut D,
+    ) -> Result<Measurement, Bmx280Error<E>>
+    where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug, D: DelayMs<u16> {
+        if self.device.is_none() {
+            self.initialize(i2c)?;
+        }
+
+        match self.measure(i2c, delay) {
+            Err(Bmx280Error::Bus(_)) | Err(Bmx280Error::Reset) => {
+                self.device = None;
+                self.initialize(i2c)?;
+                self.measure(i2c, delay)
+            },
+            result => result,
+        }
+    }
+
+    /// Detect the chip, read its calibration and configure the profile
+    fn initialize<I2C, E>(&mut self, i2c: &mut I2C) -> Result<(), Bmx280Error<E>>
+    where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug {
+        let address = self.address;
+        let chip_id = read_register(i2c, address, REGISTER_CHIP_ID)?;
+        let chip = Bmx280Chip::from_chip_id(chip_id).ok_or(Bmx280Error::UnknownChip(chip_id))?;
+        let calibration = Calibration::read(i2c, address, chip)?;
+
+        if chip == Bmx280Chip::Bme280 {
+            // Humidity settings take effect after writing ctrl_meas
+            write_register(i2c, address, REGISTER_CTRL_HUM, self.profile.humidity as u8)?;
+        }
+
+        // Config register writes may be ignored in the normal mode, the chip
+        // could have been left measuring before the MCU reset
+        write_register(i2c, address, REGISTER_CTRL_MEAS, 0)?;
+        write_register(i2c, address, REGISTER_CONFIG, self.profile.config())?;
+
+        if let Bmx280Mode::Normal(_) = self.profile.mode {
+            write_register(i2c, address, REGISTER_CTRL_MEAS, self.profile.ctrl_meas())?;
+        }
+
+        self.device = Some(Device { chip, calibration });
+        Ok(())
+    }
+
+    /// Read and compensate the last finished measurement, a new one
+    /// is started first in the forced mode
+    fn measure<I2C, E, D>(
+        &self,
+        i2c: &mut I2C,
+        delay: &mut D,
+    ) -> Result<Measurement, Bmx280Error<E>>
+    where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug, D: DelayMs<u16> {
+        let device = match self.device {
+            Some(ref device) => device,
+            None => return Err(Bmx280Error::NotReady),
+        };
+
+        if self.profile.mode == Bmx280Mode::Forced {
+            write_register(i2c, self.address, REGISTER_CTRL_MEAS, self.profile.ctrl_meas())?;
+            delay.delay_ms(self.profile.measurement_time_ms(device.chip));
+        }
+
+        // Control registers are read together with the data to detect reset,
+        // in the forced mode ctrl_meas has just been written and the config
+        // of the default profile equals its reset value, but the humidity
+        // oversampling of BME280 is cleared by a reset
+        let mut data = [0; 13];
+
+        let length = match device.chip {
+            Bmx280Chip::Bmp280 => 11,
+            Bmx280Chip::Bme280 => 13,
+        };
+
+        i2c.write_read(self.address, &[REGISTER_CTRL_HUM], &mut data[..length])
+            .map_err(Bmx280Error::Bus)?;
+
+        // Mode bits return to sleep after a forced measurement
+        let settings_mask = match self.profile.mode {
+            Bmx280Mode::Forced => !0b11,
+            Bmx280Mode::Normal(_) => 0xFF,
+        };
+
+        let humidity_lost = device.chip == Bmx280Chip::Bme280
+            && data[CTRL_HUM_OFFSET] & 0b111 != self.profile.humidity as u8;
+
+        if humidity_lost
+            || data[CTRL_MEAS_OFFSET] & settings_mask != self.profile.ctrl_meas() & settings_mask
+            || data[CONFIG_OFFSET] != self.profile.config()
+        {
+            return Err(Bmx280Error::Reset);
+        }
+
+        let data = &data[DATA_OFFSET..];
+        let raw_pressure = to_raw_20bit(&data[0..3]);
+        let raw_temperature = to_raw_20bit(&data[3..6]);
+        let raw_humidity = ((data[6] as i32) << 8) | data[7] as i32;
+
+        if raw_temperature == SKIPPED_MEASUREMENT {
+            return Err(Bmx280Error::NotReady);
+        }
+
+        // Humidity measurement was switched off, it would be compensated
+        // into a plausible value
+        if device.chip == Bmx280Chip::Bme280 && raw_humidity == SKIPPED_HUMIDITY {
+            return Err(Bmx280Error::Reset);
+        }
+
+        let calibration = &device.calibration;
+        let t_fine = calibration.t_fine(raw_temperature);
+
+        let humidity = calibration.humidity.as_ref()
+            .map(|humidity| humidity.compensate(raw_humidity, t_fine));
+
+        Ok(Measurement {
+            temperature: (t_fine*5 + 128) >> 8,
+            pressure: calibration.compensate_pressure(raw_pressure, t_fine),
+            humidity,
+        })
+    }
+}
+
+fn to_raw_20bit(data: &[u8]) -> i32 {
+    ((data[0] as i32) << 12) | ((data[1] as i32) << 4) | (data[2] as i32 >> 4)
+}
\ No newline at end of file
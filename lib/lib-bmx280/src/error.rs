@@ -0,0 +1,21 @@
+use core::fmt::{Debug, Display};
+
+pub enum Bmx280Error<E>
+where E: Debug {
+    Bus(E),
+    UnknownChip(u8),
+    NotReady,
+    /// Chip lost its configuration, it was most likely power cycled
+    Reset,
+}
+
+impl<E> Display for Bmx280Error<E> where E: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Bmx280Error::Bus(ref err) => write!(f, "Bus:{:?}", err),
+            Bmx280Error::UnknownChip(chip_id) => write!(f, "ID:{:02X}", chip_id),
+            Bmx280Error::NotReady => write!(f, "NR"),
+            Bmx280Error::Reset => write!(f, "RST"),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,13 @@
+#![no_std]
+
+mod error;
+mod registers;
+mod chip;
+mod profile;
+mod calibration;
+mod driver;
+
+pub use error::Bmx280Error;
+pub use chip::{Bmx280Chip, ADDRESS_SDO_GROUNDED, ADDRESS_SDO_HIGH};
+pub use profile::{Bmx280Profile, Bmx280Mode, Oversampling, Filter, Standby};
+pub use driver::{Bmx280, Measurement};
\ No newline at end of file
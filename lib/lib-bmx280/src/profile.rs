@@ -0,0 +1,110 @@
+use crate::chip::Bmx280Chip;
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Oversampling {
+    X1 = 0b001,
+    X2 = 0b010,
+    X4 = 0b011,
+    X8 = 0b100,
+    X16 = 0b101,
+}
+
+impl Oversampling {
+    fn samples(self) -> u32 {
+        1 << (self as u32 - 1)
+    }
+}
+
+/// Coefficient of the IIR filter smoothing out short pressure changes
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Filter {
+    Off = 0b000,
+    X2 = 0b001,
+    X4 = 0b010,
+    X8 = 0b011,
+    X16 = 0b100,
+}
+
+/// Inactive time between measurements in the normal mode
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Standby {
+    Ms0_5 = 0b000,
+    Ms62_5 = 0b001,
+    Ms125 = 0b010,
+    Ms250 = 0b011,
+    Ms500 = 0b100,
+    Ms1000 = 0b101,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Bmx280Mode {
+    /// Single measurement on every reading, the chip sleeps in between
+    Forced,
+    /// Periodic measurements, the last finished one is read
+    Normal(Standby),
+}
+
+/// Measurement settings, the presets follow the recommended modes
+/// of operation from the datasheets
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Bmx280Profile {
+    pub temperature: Oversampling,
+    pub pressure: Oversampling,
+    /// Used by BME280 only
+    pub humidity: Oversampling,
+    pub filter: Filter,
+    pub mode: Bmx280Mode,
+}
+
+impl Bmx280Profile {
+    /// Lowest power, readings are taken once per loop iteration or less often
+    pub const WEATHER_MONITORING: Self = Self {
+        temperature: Oversampling::X1,
+        pressure: Oversampling::X1,
+        humidity: Oversampling::X1,
+        filter: Filter::Off,
+        mode: Bmx280Mode::Forced,
+    };
+
+    /// Lowest noise, fast response to pressure changes
+    pub const INDOOR_NAVIGATION: Self = Self {
+        temperature: Oversampling::X2,
+        pressure: Oversampling::X16,
+        humidity: Oversampling::X1,
+        filter: Filter::X16,
+        mode: Bmx280Mode::Normal(Standby::Ms0_5),
+    };
+
+    pub(crate) fn ctrl_meas(&self) -> u8 {
+        let mode = match self.mode {
+            Bmx280Mode::Forced => 0b01,
+            Bmx280Mode::Normal(_) => 0b11,
+        };
+
+        ((self.temperature as u8) << 5) | ((self.pressure as u8) << 2) | mode
+    }
+
+    pub(crate) fn config(&self) -> u8 {
+        let standby = match self.mode {
+            Bmx280Mode::Forced => Standby::Ms0_5,
+            Bmx280Mode::Normal(standby) => standby,
+        };
+
+        ((standby as u8) << 5) | ((self.filter as u8) << 2)
+    }
+
+    /// Maximum duration of a single measurement in milliseconds
+    pub(crate) fn measurement_time_ms(&self, chip: Bmx280Chip) -> u16 {
+        let humidity = match chip {
+            Bmx280Chip::Bmp280 => 0,
+            Bmx280Chip::Bme280 => 2300*self.humidity.samples() + 575,
+        };
+
+        let microseconds = 1250
+            + 2300*self.temperature.samples()
+            + 2300*self.pressure.samples() + 575
+            + humidity;
+
+        microseconds.div_ceil(1000) as u16
+    }
+}
\ No newline at end of file
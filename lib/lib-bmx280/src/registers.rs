@@ -0,0 +1,29 @@
+use core::fmt::Debug;
+use embedded_hal::blocking::i2c;
+
+use crate::error::Bmx280Error;
+
+pub const REGISTER_CALIBRATION_TP: u8 = 0x88;
+pub const REGISTER_CALIBRATION_H1: u8 = 0xA1;
+pub const REGISTER_CHIP_ID: u8 = 0xD0;
+pub const REGISTER_CALIBRATION_H2: u8 = 0xE1;
+pub const REGISTER_CTRL_HUM: u8 = 0xF2;
+pub const REGISTER_CTRL_MEAS: u8 = 0xF4;
+pub const REGISTER_CONFIG: u8 = 0xF5;
+
+pub fn read_register<I2C, E>(i2c: &mut I2C, address: u8, register: u8) -> Result<u8, Bmx280Error<E>>
+where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug {
+    let mut value = [0];
+    i2c.write_read(address, &[register], &mut value).map_err(Bmx280Error::Bus)?;
+    Ok(value[0])
+}
+
+pub fn write_register<I2C, E>(
+    i2c: &mut I2C,
+    address: u8,
+    register: u8,
+    value: u8
+) -> Result<(), Bmx280Error<E>>
+where I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>, E: Debug {
+    i2c.write(address, &[register, value]).map_err(Bmx280Error::Bus)
+}
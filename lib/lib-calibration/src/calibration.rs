@@ -0,0 +1,154 @@
+/// Gain of an uncalibrated channel, gain is stored in 1/10000
+pub const GAIN_ONE: i32 = 10000;
+
+/// Linear correction `raw*gain + offset` of a single channel, the offset
+/// is in the units of the channel
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Calibration {
+    pub offset: i32,
+    /// In 1/10000
+    pub gain: i32,
+}
+
+impl Calibration {
+    pub const IDENTITY: Calibration = Calibration { offset: 0, gain: GAIN_ONE };
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Corrected value, rounded to the nearest unit
+    pub fn apply(&self, raw: i32) -> i32 {
+        let scaled = rounded_division(raw as i64*self.gain as i64, GAIN_ONE as i64);
+        (scaled + self.offset as i64).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+
+    /// Calibration mapping both raw values to the reference values measured
+    /// at the same time, the raw values have to differ
+    pub fn two_point(first: (i32, i32), second: (i32, i32)) -> Option<Self> {
+        let (raw_first, reference_first) = (first.0 as i64, first.1 as i64);
+        let (raw_second, reference_second) = (second.0 as i64, second.1 as i64);
+
+        if raw_first == raw_second {
+            return None;
+        }
+
+        let gain = rounded_division(
+            (reference_second - reference_first)*GAIN_ONE as i64,
+            raw_second - raw_first,
+        );
+
+        // Sensor going the other way or off by more than a factor of 10 is broken
+        if gain > 10*GAIN_ONE as i64 || gain < GAIN_ONE as i64/10 {
+            return None;
+        }
+
+        let calibration = Calibration { offset: 0, gain: gain as i32 };
+        let offset = reference_first - calibration.apply(raw_first as i32) as i64;
+
+        Some(Calibration { offset: offset as i32, gain: gain as i32 })
+    }
+}
+
+fn rounded_division(numerator: i64, denominator: i64) -> i64 {
+    let (numerator, denominator) = if denominator < 0 {
+        (-numerator, -denominator)
+    } else {
+        (numerator, denominator)
+    };
+
+    if numerator >= 0 {
+        (numerator + denominator/2)/denominator
+    } else {
+        (numerator - denominator/2)/denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration(offset: i32, gain: i32) -> Calibration {
+        Calibration { offset, gain }
+    }
+
+    #[test]
+    fn identity() {
+        for raw in [i32::MIN, -2150, 0, 2150, i32::MAX] {
+            assert_eq!(Calibration::IDENTITY.apply(raw), raw);
+        }
+
+        assert!(Calibration::IDENTITY.is_identity());
+        assert!(!calibration(1, GAIN_ONE).is_identity());
+    }
+
+    #[test]
+    fn offset_and_gain() {
+        assert_eq!(calibration(-50, GAIN_ONE).apply(2150), 2100);
+        assert_eq!(calibration(0, 12500).apply(2000), 2500);
+        assert_eq!(calibration(30, 9000).apply(-1000), -870);
+    }
+
+    #[test]
+    fn rounds_to_nearest() {
+        let slightly_high = calibration(0, 10001);
+
+        assert_eq!(slightly_high.apply(4999), 4999);
+        assert_eq!(slightly_high.apply(5000), 5001);
+        assert_eq!(slightly_high.apply(-4999), -4999);
+        assert_eq!(slightly_high.apply(-5000), -5001);
+
+        // Halves are rounded away from zero
+        assert_eq!(calibration(0, 15000).apply(3), 5);
+        assert_eq!(calibration(0, 15000).apply(-3), -5);
+    }
+
+    #[test]
+    fn clamps_to_range() {
+        assert_eq!(calibration(0, 10*GAIN_ONE).apply(i32::MAX), i32::MAX);
+        assert_eq!(calibration(0, 10*GAIN_ONE).apply(i32::MIN), i32::MIN);
+        assert_eq!(calibration(i32::MAX, GAIN_ONE).apply(1), i32::MAX);
+        assert_eq!(calibration(i32::MIN, GAIN_ONE).apply(-1), i32::MIN);
+    }
+
+    #[test]
+    fn two_point_maps_both_points() {
+        let points = [
+            ((2000, 2150), (3000, 3180)),
+            ((3000, 3180), (2000, 2150)),
+            ((-400, -380), (600, 610)),
+            ((0, 0), (1000, 1000)),
+        ];
+
+        for (first, second) in points {
+            let calibration = Calibration::two_point(first, second).unwrap();
+
+            assert_eq!(calibration.apply(first.0), first.1);
+            assert_eq!(calibration.apply(second.0), second.1);
+        }
+
+        assert_eq!(Calibration::two_point((2000, 2150), (3000, 3180)), Some(calibration(90, 10300)));
+        assert!(Calibration::two_point((0, 0), (1000, 1000)).unwrap().is_identity());
+    }
+
+    #[test]
+    fn two_point_equal_raw_values() {
+        assert_eq!(Calibration::two_point((2000, 2150), (2000, 2150)), None);
+        assert_eq!(Calibration::two_point((2000, 2150), (2000, 3180)), None);
+    }
+
+    #[test]
+    fn two_point_negative_gain() {
+        assert_eq!(Calibration::two_point((0, 100), (100, 0)), None);
+        assert_eq!(Calibration::two_point((0, 100), (100, 100)), None);
+    }
+
+    #[test]
+    fn two_point_gain_limits() {
+        assert_eq!(Calibration::two_point((0, 0), (100, 1000)), Some(calibration(0, 10*GAIN_ONE)));
+        assert_eq!(Calibration::two_point((0, 0), (100, 1001)), None);
+
+        assert_eq!(Calibration::two_point((0, 0), (100, 10)), Some(calibration(0, GAIN_ONE/10)));
+        assert_eq!(Calibration::two_point((0, 0), (100, 9)), None);
+    }
+}
\ No newline at end of file
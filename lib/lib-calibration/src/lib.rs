@@ -0,0 +1,7 @@
+#![no_std]
+
+mod calibration;
+mod table;
+
+pub use calibration::{Calibration, GAIN_ONE};
+pub use table::CalibrationTable;
\ No newline at end of file
@@ -0,0 +1,199 @@
+use crate::calibration::Calibration;
+
+/// Marks an encoded table, erased flash or a table of another layout is ignored
+const MAGIC: u32 = 0x314C_4143;
+
+/// Calibration of `N` channels which can be stored as bytes
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct CalibrationTable<const N: usize> {
+    channels: [Calibration; N],
+}
+
+impl<const N: usize> CalibrationTable<N> {
+    /// Magic, channel count, offset and gain of every channel and checksum,
+    /// all little endian
+    pub const ENCODED_LENGTH: usize = 4 + 4 + 8*N + 4;
+
+    pub const fn new() -> Self {
+        Self { channels: [Calibration::IDENTITY; N] }
+    }
+
+    pub fn get(&self, channel: usize) -> Calibration {
+        self.channels.get(channel).copied().unwrap_or(Calibration::IDENTITY)
+    }
+
+    pub fn set(&mut self, channel: usize, calibration: Calibration) {
+        if let Some(stored) = self.channels.get_mut(channel) {
+            *stored = calibration;
+        }
+    }
+
+    /// Corrected value of the channel
+    pub fn apply(&self, channel: usize, raw: i32) -> i32 {
+        self.get(channel).apply(raw)
+    }
+
+    /// Write the table to the start of `output`, `None` if it does not fit
+    pub fn encode(&self, output: &mut [u8]) -> Option<usize> {
+        let output = output.get_mut(..Self::ENCODED_LENGTH)?;
+        let mut words = core::iter::once(MAGIC)
+            .chain(core::iter::once(N as u32))
+            .chain(self.channels.iter().flat_map(|channel| [channel.offset as u32, channel.gain as u32]));
+
+        let (data, checksum) = output.split_at_mut(Self::ENCODED_LENGTH - 4);
+
+        for chunk in data.chunks_mut(4) {
+            chunk.copy_from_slice(&words.next()?.to_le_bytes());
+        }
+
+        checksum.copy_from_slice(&fnv1a(data).to_le_bytes());
+        Some(Self::ENCODED_LENGTH)
+    }
+
+    /// Table encoded at the start of `data`, `None` when there is none or it
+    /// is damaged
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let data = data.get(..Self::ENCODED_LENGTH)?;
+        let (data, checksum) = data.split_at(Self::ENCODED_LENGTH - 4);
+
+        if fnv1a(data) != read_word(checksum) {
+            return None;
+        }
+
+        if read_word(&data[0..4]) != MAGIC || read_word(&data[4..8]) != N as u32 {
+            return None;
+        }
+
+        let mut table = Self::new();
+
+        for (channel, chunk) in table.channels.iter_mut().zip(data[8..].chunks(8)) {
+            channel.offset = read_word(&chunk[0..4]) as i32;
+            channel.gain = read_word(&chunk[4..8]) as i32;
+        }
+
+        Some(table)
+    }
+}
+
+impl<const N: usize> Default for CalibrationTable<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_word(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// 32 bit FNV-1a hash
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811C_9DC5, |hash, byte| (hash ^ *byte as u32).wrapping_mul(0x0100_0193))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LENGTH: usize = CalibrationTable::<3>::ENCODED_LENGTH;
+
+    fn table() -> CalibrationTable<3> {
+        let mut table = CalibrationTable::new();
+        table.set(0, Calibration { offset: -50, gain: 10000 });
+        table.set(2, Calibration { offset: 1200, gain: 9875 });
+        table
+    }
+
+    fn encoded() -> [u8; LENGTH] {
+        let mut data = [0; LENGTH];
+        assert_eq!(table().encode(&mut data), Some(LENGTH));
+        data
+    }
+
+    /// Replace the checksum to match the modified data
+    fn update_checksum(data: &mut [u8]) {
+        let length = data.len();
+        let checksum = fnv1a(&data[..length - 4]);
+        data[length - 4..].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(CalibrationTable::decode(&encoded()), Some(table()));
+
+        let mut data = [0; CalibrationTable::<5>::ENCODED_LENGTH];
+        let _ = CalibrationTable::<5>::new().encode(&mut data);
+        assert_eq!(CalibrationTable::<5>::decode(&data), Some(CalibrationTable::new()));
+    }
+
+    #[test]
+    fn longer_buffers() {
+        let mut data = [0xFF; 2*LENGTH];
+        assert_eq!(table().encode(&mut data), Some(LENGTH));
+        assert!(data[LENGTH..].iter().all(|byte| *byte == 0xFF));
+
+        assert_eq!(CalibrationTable::decode(&data), Some(table()));
+    }
+
+    #[test]
+    fn short_buffer() {
+        let mut data = [0; LENGTH - 1];
+        assert_eq!(table().encode(&mut data), None);
+
+        assert_eq!(CalibrationTable::<3>::decode(&encoded()[..LENGTH - 1]), None);
+        assert_eq!(CalibrationTable::<3>::decode(&[]), None);
+    }
+
+    #[test]
+    fn damaged_data() {
+        for position in [0, 4, 8, 20, LENGTH - 5, LENGTH - 1] {
+            let mut data = encoded();
+            data[position] ^= 0x01;
+
+            assert_eq!(CalibrationTable::<3>::decode(&data), None);
+        }
+    }
+
+    #[test]
+    fn wrong_magic() {
+        let mut data = encoded();
+        data[0] ^= 0x01;
+        update_checksum(&mut data);
+
+        assert_eq!(CalibrationTable::<3>::decode(&data), None);
+    }
+
+    #[test]
+    fn different_channel_count() {
+        // Layout of three channels claiming to have two
+        let mut data = encoded();
+        data[4..8].copy_from_slice(&2u32.to_le_bytes());
+        update_checksum(&mut data);
+        assert_eq!(CalibrationTable::<3>::decode(&data), None);
+
+        // Table of two channels read as a table of three
+        let mut data = [0; LENGTH];
+        let _ = CalibrationTable::<2>::new().encode(&mut data);
+        assert_eq!(CalibrationTable::<3>::decode(&data), None);
+        assert_eq!(CalibrationTable::<2>::decode(&data), Some(CalibrationTable::new()));
+
+        // Table of three channels read as a table of two
+        assert_eq!(CalibrationTable::<2>::decode(&encoded()), None);
+    }
+
+    #[test]
+    fn erased_flash() {
+        assert_eq!(CalibrationTable::<3>::decode(&[0xFF; LENGTH]), None);
+        assert_eq!(CalibrationTable::<3>::decode(&[0x00; LENGTH]), None);
+    }
+
+    #[test]
+    fn channels_out_of_range() {
+        let mut table = table();
+        table.set(3, Calibration { offset: 100, gain: 10000 });
+
+        assert_eq!(table, self::table());
+        assert_eq!(table.get(3), Calibration::IDENTITY);
+        assert_eq!(table.apply(3, 2150), 2150);
+        assert_eq!(table.apply(0, 2150), 2100);
+    }
+}
\ No newline at end of file
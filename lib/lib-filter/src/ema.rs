@@ -0,0 +1,122 @@
+/// Fractional bits of the filter state, so that small weights still move
+/// the output on the smallest input change
+const STATE_SHIFT: u32 = 8;
+
+/// Exponential moving average, every new value contributes `weight` percent
+pub struct Ema {
+    weight: i64,
+    /// Average with `STATE_SHIFT` fractional bits
+    state: Option<i64>,
+}
+
+impl Ema {
+    /// `weight` is clamped to 1-100 percent, 100 passes the values through
+    pub const fn new(weight: u8) -> Self {
+        let weight = if weight == 0 { 1 } else if weight > 100 { 100 } else { weight };
+        Self { weight: weight as i64, state: None }
+    }
+
+    /// Add the value and return the average rounded to the nearest unit,
+    /// the first value initializes the average
+    pub fn update(&mut self, value: i32) -> i32 {
+        let value = (value as i64) << STATE_SHIFT;
+
+        let state = match self.state {
+            Some(state) => state + rounded_division((value - state)*self.weight, 100),
+            None => value,
+        };
+
+        self.state = Some(state);
+        rounded_division(state, 1 << STATE_SHIFT) as i32
+    }
+
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+fn rounded_division(numerator: i64, denominator: i64) -> i64 {
+    if numerator >= 0 {
+        (numerator + denominator/2)/denominator
+    } else {
+        (numerator - denominator/2)/denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_value_initializes() {
+        let mut ema = Ema::new(10);
+
+        assert_eq!(ema.update(-1234), -1234);
+        assert_eq!(ema.update(-1234), -1234);
+    }
+
+    #[test]
+    fn step_response() {
+        let mut ema = Ema::new(50);
+        ema.update(0);
+        let output = [1000; 6].map(|value| ema.update(value));
+
+        assert_eq!(output, [500, 750, 875, 938, 969, 984]);
+    }
+
+    #[test]
+    fn small_weight_reaches_the_input() {
+        let mut ema = Ema::new(1);
+        ema.update(0);
+        let mut output = 0;
+
+        for _ in 0..2000 {
+            output = ema.update(10);
+        }
+
+        assert_eq!(output, 10);
+    }
+
+    #[test]
+    fn smooths_noise() {
+        let mut ema = Ema::new(20);
+        ema.update(2000);
+
+        // Square wave of +-100 around 2000
+        for step in 0..200 {
+            let output = ema.update(if step % 2 == 0 { 2100 } else { 1900 });
+            assert!((1870..=2130).contains(&output), "step {} output {}", step, output);
+        }
+
+        let output = ema.update(2100);
+        assert!((2000..=2030).contains(&output), "output {}", output);
+    }
+
+    #[test]
+    fn weight_is_clamped() {
+        let mut ema = Ema::new(0);
+        ema.update(0);
+        assert_eq!(ema.update(100), 1);
+
+        let mut ema = Ema::new(255);
+        ema.update(0);
+        assert_eq!(ema.update(100), 100);
+    }
+
+    #[test]
+    fn extreme_values() {
+        let mut ema = Ema::new(50);
+
+        assert_eq!(ema.update(i32::MAX), i32::MAX);
+        assert_eq!(ema.update(i32::MIN), -1);
+    }
+
+    #[test]
+    fn reset_forgets_average() {
+        let mut ema = Ema::new(10);
+        ema.update(500);
+        ema.reset();
+
+        assert_eq!(ema.update(-20), -20);
+    }
+}
\ No newline at end of file
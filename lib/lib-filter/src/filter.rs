@@ -0,0 +1,127 @@
+use crate::{median::Median, ema::Ema, spike::SpikeRejector};
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct SpikeConfig {
+    /// Largest accepted change between two values
+    pub max_jump: i32,
+    /// Rejections in a row after which the new level is accepted
+    pub max_rejections: u8,
+}
+
+/// Stages of a channel filter, the enabled ones run in the order
+/// spike rejection, median, moving average
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct FilterConfig {
+    pub spike: Option<SpikeConfig>,
+    pub median: bool,
+    /// Weight of a new value in percent
+    pub ema: Option<u8>,
+}
+
+impl FilterConfig {
+    /// Values are passed through
+    pub const NONE: FilterConfig = FilterConfig { spike: None, median: false, ema: None };
+}
+
+/// Filter of a single channel with median of `N` values, the state has
+/// a fixed size
+pub struct Filter<const N: usize> {
+    spike: Option<SpikeRejector>,
+    median: Option<Median<N>>,
+    ema: Option<Ema>,
+}
+
+impl<const N: usize> Filter<N> {
+    pub const fn new(config: FilterConfig) -> Self {
+        Self {
+            spike: match config.spike {
+                Some(spike) => Some(SpikeRejector::new(spike.max_jump, spike.max_rejections)),
+                None => None,
+            },
+            median: if config.median { Some(Median::new()) } else { None },
+            ema: match config.ema {
+                Some(weight) => Some(Ema::new(weight)),
+                None => None,
+            },
+        }
+    }
+
+    pub fn update(&mut self, value: i32) -> i32 {
+        let value = self.spike.as_mut().map_or(value, |spike| spike.update(value));
+        let value = self.median.as_mut().map_or(value, |median| median.update(value));
+        self.ema.as_mut().map_or(value, |ema| ema.update(value))
+    }
+
+    /// Forget the history, e.g. after the sensor was missing for a long time
+    pub fn reset(&mut self) {
+        if let Some(spike) = self.spike.as_mut() {
+            spike.reset();
+        }
+
+        if let Some(median) = self.median.as_mut() {
+            median.reset();
+        }
+
+        if let Some(ema) = self.ema.as_mut() {
+            ema.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: FilterConfig = FilterConfig {
+        spike: Some(SpikeConfig { max_jump: 100, max_rejections: 2 }),
+        median: true,
+        ema: Some(50),
+    };
+
+    #[test]
+    fn none_passes_values() {
+        let mut filter = Filter::<5>::new(FilterConfig::NONE);
+        let input = [0, 5000, -5000, 7];
+
+        assert_eq!(input.map(|value| filter.update(value)), input);
+    }
+
+    #[test]
+    fn noisy_signal_with_spikes() {
+        let mut filter = Filter::<3>::new(ALL);
+
+        // Slow ramp with alternating noise and occasional spikes
+        for step in 0..100 {
+            let noise = if step % 2 == 0 { 20 } else { -20 };
+            let spike = if step % 17 == 5 { 3000 } else { 0 };
+            let output = filter.update(2000 + step*2 + noise + spike);
+
+            assert!((output - (2000 + step*2)).abs() <= 25, "step {} output {}", step, output);
+        }
+    }
+
+    #[test]
+    fn real_step_is_followed() {
+        let mut filter = Filter::<3>::new(ALL);
+
+        for _ in 0..10 {
+            filter.update(0);
+        }
+
+        let output = [1000; 8].map(|value| filter.update(value));
+
+        // Two rejections, a median of three and the moving average
+        assert_eq!(output[..3], [0, 0, 0]);
+        assert_eq!(output[3..], [500, 750, 875, 938, 969]);
+    }
+
+    #[test]
+    fn reset_restarts_all_stages() {
+        let mut filter = Filter::<3>::new(ALL);
+        filter.update(0);
+        filter.update(0);
+        filter.reset();
+
+        assert_eq!(filter.update(1000), 1000);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+#![no_std]
+
+mod median;
+mod ema;
+mod spike;
+mod filter;
+
+pub use median::Median;
+pub use ema::Ema;
+pub use spike::SpikeRejector;
+pub use filter::{Filter, FilterConfig, SpikeConfig};
\ No newline at end of file
@@ -0,0 +1,93 @@
+/// Median of the last `N` values, fewer values are used until `N` arrive
+pub struct Median<const N: usize> {
+    samples: [i32; N],
+    count: usize,
+    next: usize,
+}
+
+impl<const N: usize> Median<N> {
+    pub const fn new() -> Self {
+        Self { samples: [0; N], count: 0, next: 0 }
+    }
+
+    /// Add the value and return the median, the lower of the two middle
+    /// values is returned for an even count
+    pub fn update(&mut self, value: i32) -> i32 {
+        if N == 0 {
+            return value;
+        }
+
+        self.samples[self.next] = value;
+        self.next = (self.next + 1) % N;
+        self.count = (self.count + 1).min(N);
+
+        let mut sorted = self.samples;
+        let sorted = &mut sorted[..self.count];
+        sorted.sort_unstable();
+        sorted[(self.count - 1)/2]
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.next = 0;
+    }
+}
+
+impl<const N: usize> Default for Median<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_single_outliers() {
+        let mut median = Median::<5>::new();
+        let input = [200, 201, 900, 202, 201, -500, 203, 202];
+        let output = input.map(|value| median.update(value));
+
+        assert_eq!(output, [200, 200, 201, 201, 201, 201, 202, 202]);
+    }
+
+    #[test]
+    fn follows_a_step_after_half_the_length() {
+        let mut median = Median::<5>::new();
+
+        for _ in 0..5 {
+            median.update(0);
+        }
+
+        let output = [100; 5].map(|value| median.update(value));
+
+        assert_eq!(output, [0, 0, 100, 100, 100]);
+    }
+
+    #[test]
+    fn lower_middle_value_for_even_length() {
+        let mut median = Median::<4>::new();
+        let output = [10, 40, 30, 20, 50].map(|value| median.update(value));
+
+        assert_eq!(output, [10, 10, 30, 20, 30]);
+    }
+
+    #[test]
+    fn reset_forgets_history() {
+        let mut median = Median::<3>::new();
+        median.update(1000);
+        median.update(1000);
+        median.reset();
+
+        assert_eq!(median.update(5), 5);
+        assert_eq!(median.update(7), 5);
+    }
+
+    #[test]
+    fn zero_length_passes_values() {
+        let mut median = Median::<0>::new();
+
+        assert_eq!(median.update(42), 42);
+    }
+}
\ No newline at end of file
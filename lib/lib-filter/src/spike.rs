@@ -0,0 +1,103 @@
+/// Replaces a value jumping more than `max_jump` from the previous accepted
+/// one by the previous value, after `max_rejections` rejections in a row
+/// the jump is taken as a real step and accepted
+pub struct SpikeRejector {
+    max_jump: i32,
+    max_rejections: u8,
+    last: Option<i32>,
+    rejections: u8,
+}
+
+impl SpikeRejector {
+    pub const fn new(max_jump: i32, max_rejections: u8) -> Self {
+        Self { max_jump, max_rejections, last: None, rejections: 0 }
+    }
+
+    pub fn update(&mut self, value: i32) -> i32 {
+        match self.last {
+            Some(last) if (value as i64 - last as i64).abs() > self.max_jump as i64
+                && self.rejections < self.max_rejections => {
+                self.rejections += 1;
+                last
+            },
+            _ => {
+                self.last = Some(value);
+                self.rejections = 0;
+                value
+            },
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.rejections = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_single_spikes() {
+        let mut spike = SpikeRejector::new(50, 2);
+        let input = [2000, 2010, 2500, 2020, 1400, 2030, 2080, 2150];
+        let output = input.map(|value| spike.update(value));
+
+        assert_eq!(output, [2000, 2010, 2010, 2020, 2020, 2030, 2080, 2080]);
+    }
+
+    #[test]
+    fn accepts_step_after_max_rejections() {
+        let mut spike = SpikeRejector::new(50, 3);
+        spike.update(1000);
+        let output = [3000, 3000, 3000, 3000, 3010].map(|value| spike.update(value));
+
+        assert_eq!(output, [1000, 1000, 1000, 3000, 3010]);
+    }
+
+    #[test]
+    fn rejections_count_in_a_row() {
+        let mut spike = SpikeRejector::new(50, 2);
+        spike.update(1000);
+        let output = [3000, 1020, 3000, 1040, 3000, 3000, 3000].map(|value| spike.update(value));
+
+        // The accepted value in between restarts the count
+        assert_eq!(output, [1000, 1020, 1020, 1040, 1040, 1040, 3000]);
+    }
+
+    #[test]
+    fn zero_rejections_pass_everything() {
+        let mut spike = SpikeRejector::new(0, 0);
+        let output = [0, 100, -100].map(|value| spike.update(value));
+
+        assert_eq!(output, [0, 100, -100]);
+    }
+
+    #[test]
+    fn jump_at_the_limit_is_accepted() {
+        let mut spike = SpikeRejector::new(50, 1);
+        spike.update(0);
+
+        assert_eq!(spike.update(50), 50);
+        assert_eq!(spike.update(-1), 50);
+    }
+
+    #[test]
+    fn extreme_values_do_not_overflow() {
+        let mut spike = SpikeRejector::new(i32::MAX, 1);
+        spike.update(i32::MIN);
+
+        assert_eq!(spike.update(i32::MAX), i32::MIN);
+        assert_eq!(spike.update(i32::MAX), i32::MAX);
+    }
+
+    #[test]
+    fn reset_accepts_any_value() {
+        let mut spike = SpikeRejector::new(10, 5);
+        spike.update(0);
+        spike.reset();
+
+        assert_eq!(spike.update(1000), 1000);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,171 @@
+use core::fmt::Display;
+
+pub const FAULT_COUNT: usize = 9;
+
+/// Reason of a failed or implausible reading, communication faults come
+/// from the drivers, the others are found by `ChannelMonitor`
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Fault {
+    /// Sensor did not answer at all
+    NoResponse,
+    /// Transfer started but did not finish
+    Timeout,
+    CrcMismatch,
+    /// Bus or pin error, or a driver error without more details
+    Bus,
+    /// Driver has no value without telling why
+    Missing,
+    /// Sensor answers but reports broken wiring, an open or shorted probe
+    Wiring,
+    /// Value outside the physically possible range
+    Range,
+    /// Reading has not changed for too long
+    Stuck,
+    /// Change since the previous reading is faster than possible
+    Rate,
+}
+
+impl Fault {
+    pub const ALL: [Fault; FAULT_COUNT] = [
+        Fault::NoResponse,
+        Fault::Timeout,
+        Fault::CrcMismatch,
+        Fault::Bus,
+        Fault::Missing,
+        Fault::Wiring,
+        Fault::Range,
+        Fault::Stuck,
+        Fault::Rate,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Communication faults mean there is no value at all
+    pub fn is_communication(&self) -> bool {
+        !matches!(self, Fault::Range | Fault::Stuck | Fault::Rate)
+    }
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Fault::NoResponse => write!(f, "NR"),
+            Fault::Timeout => write!(f, "TO"),
+            Fault::CrcMismatch => write!(f, "CRC"),
+            Fault::Bus => write!(f, "BUS"),
+            Fault::Missing => write!(f, "NA"),
+            Fault::Wiring => write!(f, "WIR"),
+            Fault::Range => write!(f, "RNG"),
+            Fault::Stuck => write!(f, "STK"),
+            Fault::Rate => write!(f, "JMP"),
+        }
+    }
+}
+
+/// Set of faults of a single reading
+#[derive(PartialEq, Eq, Copy, Clone, Default, Debug)]
+pub struct Quality(u16);
+
+impl Quality {
+    pub const GOOD: Quality = Quality(0);
+
+    pub const fn with(self, fault: Fault) -> Self {
+        Quality(self.0 | 1 << fault as u16)
+    }
+
+    pub fn contains(&self, fault: Fault) -> bool {
+        self.0 & 1 << fault as u16 != 0
+    }
+
+    pub fn is_good(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn faults(self) -> impl Iterator<Item = Fault> {
+        Fault::ALL.into_iter().filter(move |fault| self.contains(*fault))
+    }
+}
+
+/// "OK" or the codes of all faults joined by "+"
+impl Display for Quality {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_good() {
+            return write!(f, "OK");
+        }
+
+        for (index, fault) in self.faults().enumerate() {
+            if index > 0 {
+                write!(f, "+")?;
+            }
+
+            write!(f, "{}", fault)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{format, vec::Vec};
+
+    #[test]
+    fn indexes_follow_all() {
+        for (index, fault) in Fault::ALL.iter().enumerate() {
+            assert_eq!(fault.index(), index);
+        }
+    }
+
+    #[test]
+    fn quality_flags() {
+        let quality = Quality::GOOD.with(Fault::Range).with(Fault::Stuck);
+
+        assert!(quality.contains(Fault::Range));
+        assert!(quality.contains(Fault::Stuck));
+        assert!(!quality.contains(Fault::Rate));
+        assert!(!quality.is_good());
+        assert_eq!(quality.faults().collect::<Vec<_>>(), [Fault::Range, Fault::Stuck]);
+
+        // Adding a fault twice does not change anything
+        assert_eq!(quality.with(Fault::Range), quality);
+    }
+
+    #[test]
+    fn every_fault_fits() {
+        let mut quality = Quality::GOOD;
+
+        for fault in Fault::ALL {
+            quality = quality.with(fault);
+        }
+
+        assert_eq!(quality.faults().collect::<Vec<_>>(), Fault::ALL);
+    }
+
+    #[test]
+    fn good_quality() {
+        assert!(Quality::GOOD.is_good());
+        assert!(Quality::default().is_good());
+        assert_eq!(Quality::GOOD.faults().count(), 0);
+        assert_eq!(format!("{}", Quality::GOOD), "OK");
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", Quality::GOOD.with(Fault::Wiring)), "WIR");
+        assert_eq!(format!("{}", Quality::GOOD.with(Fault::Rate).with(Fault::Range)), "RNG+JMP");
+
+        let codes = Fault::ALL.map(|fault| format!("{}", fault));
+        assert_eq!(codes, ["NR", "TO", "CRC", "BUS", "NA", "WIR", "RNG", "STK", "JMP"]);
+    }
+
+    #[test]
+    fn communication_faults() {
+        let communication = Fault::ALL.map(|fault| fault.is_communication());
+        assert_eq!(communication, [true, true, true, true, true, true, false, false, false]);
+    }
+}
\ No newline at end of file
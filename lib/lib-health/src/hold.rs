@@ -0,0 +1,168 @@
+/// Value returned by `Hold`, the age is set when the value comes from
+/// an earlier reading
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Held<T> {
+    pub value: T,
+    /// Seconds since the value was read
+    pub age: Option<u32>,
+}
+
+impl<T> Held<T> {
+    pub fn is_stale(&self) -> bool {
+        self.age.is_some()
+    }
+}
+
+/// Keeps the last good value of a channel, a failed reading is replaced by
+/// it for `window` seconds, then the failure is passed through
+pub struct Hold<T> {
+    window: u32,
+    /// Last good value with the time it was read at
+    last: Option<(T, u32)>,
+}
+
+impl<T> Hold<T>
+where T: Copy {
+    pub const fn new(window: u32) -> Self {
+        Self { window, last: None }
+    }
+
+    /// Pass `reading` taken at `time` (monotonic seconds) through, or replace
+    /// a failure by the last good value while it is not older than the window,
+    /// nothing is held without time or when the clock went back
+    pub fn update<E>(&mut self, reading: Result<T, E>, time: Option<u32>) -> Result<Held<T>, E> {
+        match reading {
+            Ok(value) => {
+                self.last = time.map(|time| (value, time));
+                Ok(Held { value, age: None })
+            },
+            Err(error) => match (self.last, time) {
+                (Some((value, read_at)), Some(time))
+                if time >= read_at && time - read_at <= self.window => {
+                    Ok(Held { value, age: Some(time - read_at) })
+                },
+                _ => {
+                    self.last = None;
+                    Err(error)
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Same as the stale window of the DHT sensors
+    const WINDOW: u32 = 60;
+
+    fn held(value: i16, age: Option<u32>) -> Result<Held<i16>, ()> {
+        Ok(Held { value, age })
+    }
+
+    #[test]
+    fn good_readings_pass_through() {
+        let mut hold = Hold::new(WINDOW);
+
+        assert_eq!(hold.update(Ok(215), Some(100)), held(215, None));
+        assert_eq!(hold.update(Ok(216), Some(102)), held(216, None));
+        assert!(!hold.update::<()>(Ok(217), Some(104)).unwrap().is_stale());
+    }
+
+    #[test]
+    fn failures_within_the_window() {
+        let mut hold = Hold::new(WINDOW);
+        let _ = hold.update::<()>(Ok(215), Some(1000));
+
+        for age in [2, 10, 30, WINDOW] {
+            let result = hold.update(Err(()), Some(1000 + age));
+            assert_eq!(result, held(215, Some(age)));
+            assert!(result.unwrap().is_stale());
+        }
+    }
+
+    #[test]
+    fn failures_beyond_the_window() {
+        let mut hold = Hold::new(WINDOW);
+        let _ = hold.update::<()>(Ok(215), Some(1000));
+
+        assert_eq!(hold.update(Err(()), Some(1000 + WINDOW)), held(215, Some(WINDOW)));
+        assert_eq!(hold.update(Err(()), Some(1001 + WINDOW)), Err(()));
+
+        // The value is dropped, not held again once the time fits
+        assert_eq!(hold.update(Err(()), Some(1000 + WINDOW)), Err(()));
+    }
+
+    #[test]
+    fn first_reading_fails() {
+        let mut hold = Hold::<i16>::new(WINDOW);
+
+        assert_eq!(hold.update(Err(()), Some(1000)), Err(()));
+    }
+
+    #[test]
+    fn recovery_restarts_the_window() {
+        let mut hold = Hold::new(WINDOW);
+        let _ = hold.update::<()>(Ok(215), Some(1000));
+        let _ = hold.update(Err(()), Some(1050));
+        let _ = hold.update(Err(()), Some(1100));
+
+        assert_eq!(hold.update(Ok(220), Some(1102)), held(220, None));
+        assert_eq!(hold.update(Err(()), Some(1150)), held(220, Some(48)));
+        assert_eq!(hold.update(Err(()), Some(1162)), held(220, Some(WINDOW)));
+    }
+
+    #[test]
+    fn errors_are_passed_through() {
+        let mut hold = Hold::<i16>::new(WINDOW);
+
+        assert_eq!(hold.update(Err("timeout"), Some(10)), Err("timeout"));
+        assert_eq!(hold.update::<&str>(Ok(1), Some(10)), Ok(Held { value: 1, age: None }));
+        assert_eq!(hold.update(Err("crc"), Some(100)), Err("crc"));
+    }
+
+    #[test]
+    fn nothing_is_held_without_time() {
+        let mut hold = Hold::new(WINDOW);
+
+        let _ = hold.update::<()>(Ok(215), None);
+        assert_eq!(hold.update(Err(()), Some(1000)), Err(()));
+
+        let _ = hold.update::<()>(Ok(215), Some(1000));
+        assert_eq!(hold.update(Err(()), None), Err(()));
+        assert_eq!(hold.update(Err(()), Some(1001)), Err(()));
+    }
+
+    #[test]
+    fn clock_going_back() {
+        let mut hold = Hold::new(WINDOW);
+        let _ = hold.update::<()>(Ok(215), Some(1000));
+
+        assert_eq!(hold.update(Err(()), Some(999)), Err(()));
+        assert_eq!(hold.update(Err(()), Some(1001)), Err(()));
+    }
+
+    #[test]
+    fn clock_rollover() {
+        let mut hold = Hold::new(WINDOW);
+
+        // Value read just before the counter wraps is not held after it
+        let _ = hold.update::<()>(Ok(215), Some(u32::MAX - 10));
+        assert_eq!(hold.update(Err(()), Some(u32::MAX)), held(215, Some(10)));
+        assert_eq!(hold.update(Err(()), Some(5)), Err(()));
+
+        // Holding works again with readings after the rollover
+        let _ = hold.update::<()>(Ok(216), Some(6));
+        assert_eq!(hold.update(Err(()), Some(6 + WINDOW)), held(216, Some(WINDOW)));
+    }
+
+    #[test]
+    fn zero_window() {
+        let mut hold = Hold::new(0);
+        let _ = hold.update::<()>(Ok(215), Some(1000));
+
+        assert_eq!(hold.update(Err(()), Some(1000)), held(215, Some(0)));
+        assert_eq!(hold.update(Err(()), Some(1001)), Err(()));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+#![no_std]
+
+mod fault;
+mod monitor;
+mod hold;
+
+pub use fault::{Fault, Quality, FAULT_COUNT};
+pub use monitor::{Limits, ChannelConfig, ChannelHealth, ChannelMonitor};
+pub use hold::{Hold, Held};
\ No newline at end of file
@@ -0,0 +1,306 @@
+use crate::fault::{Fault, Quality, FAULT_COUNT};
+
+/// Plausibility limits of a single measured quantity
+#[derive(Copy, Clone, Debug)]
+pub struct Limits {
+    pub minimum: i32,
+    pub maximum: i32,
+    /// Largest plausible change per minute, 0 disables the check
+    pub max_rate: i32,
+}
+
+/// Limits of the `Q` quantities of a channel in the order they are passed
+/// to `ChannelMonitor::update`
+#[derive(Copy, Clone, Debug)]
+pub struct ChannelConfig<const Q: usize> {
+    pub limits: [Limits; Q],
+    /// Reading which has not changed at all for this many seconds is stuck,
+    /// 0 disables the check
+    pub stuck_seconds: u32,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Default, Debug)]
+pub struct ChannelHealth {
+    pub readings: u32,
+    /// Number of readings without any fault
+    pub good: u32,
+    /// Number of readings with every fault, indexed by `Fault::index`
+    pub faults: [u32; FAULT_COUNT],
+    pub consecutive_failures: u32,
+    /// Seconds since the last reading without any fault
+    pub since_good: Option<u32>,
+    /// Quality of the latest reading
+    pub quality: Quality,
+}
+
+impl ChannelHealth {
+    pub fn good_percentage(&self) -> Option<u32> {
+        match self.readings {
+            0 => None,
+            readings => Some((100*self.good as u64/readings as u64) as u32),
+        }
+    }
+}
+
+/// Health statistics and plausibility checks of a channel measuring `Q`
+/// quantities at once
+pub struct ChannelMonitor<const Q: usize> {
+    config: ChannelConfig<Q>,
+    health: ChannelHealth,
+    last_good: Option<u32>,
+    /// Previous values with the time they were read at
+    previous: Option<([i32; Q], Option<u32>)>,
+    unchanged_since: Option<u32>,
+}
+
+impl<const Q: usize> ChannelMonitor<Q> {
+    pub const fn new(config: ChannelConfig<Q>) -> Self {
+        Self {
+            config,
+            health: ChannelHealth {
+                readings: 0,
+                good: 0,
+                faults: [0; FAULT_COUNT],
+                consecutive_failures: 0,
+                since_good: None,
+                quality: Quality::GOOD,
+            },
+            last_good: None,
+            previous: None,
+            unchanged_since: None,
+        }
+    }
+
+    /// Count the reading taken at `time` (monotonic seconds), implausible
+    /// values are counted as failures, but they are still returned by the
+    /// drivers, so the quality is only a flag next to them
+    pub fn update(&mut self, reading: Result<[i32; Q], Fault>, time: Option<u32>) -> Quality {
+        let quality = match reading {
+            Ok(values) => self.check(values, time),
+            Err(fault) => Quality::GOOD.with(fault),
+        };
+
+        let health = &mut self.health;
+        health.readings = health.readings.saturating_add(1);
+
+        for fault in quality.faults() {
+            health.faults[fault.index()] = health.faults[fault.index()].saturating_add(1);
+        }
+
+        if quality.is_good() {
+            health.good = health.good.saturating_add(1);
+            health.consecutive_failures = 0;
+            self.last_good = time;
+        } else {
+            health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+        }
+
+        health.since_good = match (self.last_good, time) {
+            (Some(last_good), Some(time)) => Some(time.saturating_sub(last_good)),
+            _ => None,
+        };
+
+        health.quality = quality;
+        quality
+    }
+
+    pub fn health(&self) -> ChannelHealth {
+        self.health
+    }
+
+    fn check(&mut self, values: [i32; Q], time: Option<u32>) -> Quality {
+        let mut quality = Quality::GOOD;
+        let limits = &self.config.limits;
+
+        let out_of_range = values.iter().zip(limits.iter())
+            .any(|(value, limits)| *value < limits.minimum || *value > limits.maximum);
+
+        if out_of_range {
+            quality = quality.with(Fault::Range);
+        }
+
+        if let (Some((previous, Some(previous_time))), Some(time)) = (self.previous, time) {
+            // Readings within the same second are compared as a second apart
+            let seconds = time.saturating_sub(previous_time).max(1) as i64;
+
+            let too_fast = values.iter().zip(previous.iter()).zip(limits.iter())
+                .any(|((value, previous), limits)| {
+                    let change = (*value as i64 - *previous as i64).abs();
+                    limits.max_rate > 0 && change*60 > limits.max_rate as i64*seconds
+                });
+
+            if too_fast {
+                quality = quality.with(Fault::Rate);
+            }
+        }
+
+        let unchanged = matches!(self.previous, Some((previous, _)) if previous == values);
+
+        if !unchanged || self.unchanged_since.is_none() {
+            self.unchanged_since = time;
+        }
+
+        if let (Some(since), Some(time)) = (self.unchanged_since, time) {
+            if self.config.stuck_seconds > 0 && time.saturating_sub(since) >= self.config.stuck_seconds {
+                quality = quality.with(Fault::Stuck);
+            }
+        }
+
+        self.previous = Some((values, time));
+        quality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPERATURE: Limits = Limits { minimum: -400, maximum: 800, max_rate: 20 };
+    const HUMIDITY: Limits = Limits { minimum: 0, maximum: 1000, max_rate: 0 };
+
+    fn monitor(stuck_seconds: u32) -> ChannelMonitor<2> {
+        ChannelMonitor::new(ChannelConfig { limits: [TEMPERATURE, HUMIDITY], stuck_seconds })
+    }
+
+    #[test]
+    fn range_limits() {
+        let mut monitor = monitor(0);
+
+        for values in [[-400, 0], [800, 1000], [215, 450]] {
+            assert!(monitor.update(Ok(values), None).is_good());
+        }
+
+        for values in [[-401, 450], [801, 450], [215, -1], [215, 1001]] {
+            assert_eq!(monitor.update(Ok(values), None), Quality::GOOD.with(Fault::Range));
+        }
+    }
+
+    #[test]
+    fn max_rate() {
+        let mut monitor = monitor(0);
+        let _ = monitor.update(Ok([200, 450]), Some(1000));
+
+        // 20 per minute allows 10 in 30 seconds
+        assert!(monitor.update(Ok([210, 450]), Some(1030)).is_good());
+        assert_eq!(monitor.update(Ok([221, 450]), Some(1060)), Quality::GOOD.with(Fault::Rate));
+
+        // Decreasing values are limited the same way
+        assert_eq!(monitor.update(Ok([200, 450]), Some(1090)), Quality::GOOD.with(Fault::Rate));
+        assert!(monitor.update(Ok([190, 450]), Some(1120)).is_good());
+    }
+
+    #[test]
+    fn max_rate_within_the_same_second() {
+        let mut monitor = monitor(0);
+        let _ = monitor.update(Ok([200, 450]), Some(1000));
+
+        // Compared as a second apart, 20 per minute is not even 1 per second
+        assert_eq!(monitor.update(Ok([201, 450]), Some(1000)), Quality::GOOD.with(Fault::Rate));
+    }
+
+    #[test]
+    fn max_rate_disabled() {
+        let mut monitor = monitor(0);
+        let _ = monitor.update(Ok([200, 0]), Some(1000));
+
+        // Humidity has no rate limit
+        assert!(monitor.update(Ok([200, 1000]), Some(1001)).is_good());
+
+        // Without time the rate is unknown
+        let _ = monitor.update(Ok([200, 0]), None);
+        assert!(monitor.update(Ok([700, 0]), None).is_good());
+    }
+
+    #[test]
+    fn max_rate_after_failures() {
+        let mut monitor = monitor(0);
+        let _ = monitor.update(Ok([200, 450]), Some(1000));
+        let _ = monitor.update(Err(Fault::Timeout), Some(1030));
+
+        // Change is compared with the last value over the whole time
+        assert!(monitor.update(Ok([220, 450]), Some(1060)).is_good());
+    }
+
+    #[test]
+    fn stuck_seconds() {
+        let mut monitor = monitor(300);
+
+        for time in [1000, 1100, 1299] {
+            assert!(monitor.update(Ok([215, 450]), Some(time)).is_good());
+        }
+
+        assert_eq!(monitor.update(Ok([215, 450]), Some(1300)), Quality::GOOD.with(Fault::Stuck));
+        assert_eq!(monitor.update(Ok([215, 450]), Some(2000)), Quality::GOOD.with(Fault::Stuck));
+
+        // Change of any of the quantities restarts the time
+        assert!(monitor.update(Ok([215, 451]), Some(2010)).is_good());
+        assert!(monitor.update(Ok([215, 451]), Some(2309)).is_good());
+        assert_eq!(monitor.update(Ok([215, 451]), Some(2310)), Quality::GOOD.with(Fault::Stuck));
+    }
+
+    #[test]
+    fn stuck_seconds_disabled() {
+        let mut monitor = monitor(0);
+
+        for time in (0..100_000).step_by(1000) {
+            assert!(monitor.update(Ok([215, 450]), Some(time)).is_good());
+        }
+    }
+
+    #[test]
+    fn several_faults_at_once() {
+        let mut monitor = monitor(0);
+        let _ = monitor.update(Ok([200, 450]), Some(1000));
+
+        let quality = monitor.update(Ok([900, 450]), Some(1001));
+        assert_eq!(quality, Quality::GOOD.with(Fault::Range).with(Fault::Rate));
+
+        let health = monitor.health();
+        assert_eq!(health.faults[Fault::Range.index()], 1);
+        assert_eq!(health.faults[Fault::Rate.index()], 1);
+        assert_eq!(health.good, 1);
+    }
+
+    #[test]
+    fn fault_counting() {
+        let mut monitor = monitor(0);
+        assert_eq!(monitor.health().good_percentage(), None);
+
+        let _ = monitor.update(Ok([215, 450]), Some(1000));
+        let _ = monitor.update(Err(Fault::NoResponse), Some(1010));
+        let _ = monitor.update(Err(Fault::CrcMismatch), Some(1020));
+        let _ = monitor.update(Err(Fault::CrcMismatch), Some(1030));
+
+        let health = monitor.health();
+        assert_eq!(health.readings, 4);
+        assert_eq!(health.good, 1);
+        assert_eq!(health.good_percentage(), Some(25));
+        assert_eq!(health.consecutive_failures, 3);
+        assert_eq!(health.since_good, Some(30));
+        assert_eq!(health.quality, Quality::GOOD.with(Fault::CrcMismatch));
+
+        let mut expected = [0; FAULT_COUNT];
+        expected[Fault::NoResponse.index()] = 1;
+        expected[Fault::CrcMismatch.index()] = 2;
+        assert_eq!(health.faults, expected);
+
+        // Good reading ends the failures but keeps the counts
+        let _ = monitor.update(Ok([215, 450]), Some(1040));
+        let health = monitor.health();
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.since_good, Some(0));
+        assert_eq!(health.faults, expected);
+        assert!(health.quality.is_good());
+    }
+
+    #[test]
+    fn fault_counting_without_time() {
+        let mut monitor = monitor(0);
+        let _ = monitor.update(Ok([215, 450]), None);
+        let _ = monitor.update(Err(Fault::Missing), None);
+
+        let health = monitor.health();
+        assert_eq!(health.since_good, None);
+        assert_eq!(health.quality, Quality::GOOD.with(Fault::Missing));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,18 @@
+use core::fmt::Display;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum NtcError {
+    /// ADC reads close to the rail where the thermistor resistance would be infinite
+    OpenCircuit,
+    /// ADC reads close to the rail where the thermistor resistance would be zero
+    ShortCircuit,
+}
+
+impl Display for NtcError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NtcError::OpenCircuit => write!(f, "OC"),
+            NtcError::ShortCircuit => write!(f, "SC"),
+        }
+    }
+}
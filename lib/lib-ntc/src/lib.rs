@@ -0,0 +1,9 @@
+#![no_std]
+
+mod error;
+mod log;
+mod ntc;
+
+pub use error::NtcError;
+pub use log::{log2_q16, ln_q16};
+pub use ntc::{NtcConfig, NtcModel, Divider, resistance_milliohms, temperature_centidegrees};
@@ -0,0 +1,28 @@
+/// Natural logarithm of 2 in Q16
+const LN_2_Q16: i64 = 45426;
+
+/// Base 2 logarithm of a non-zero `value` in Q16 fixed point
+pub fn log2_q16(value: u64) -> i64 {
+    let integer = 63 - value.leading_zeros() as i64;
+
+    // Mantissa in [1, 2) as Q62, squaring it doubles the logarithm,
+    // every time it overflows 2 the next fractional bit is one
+    let mut mantissa = (value << (63 - integer)) as u128 >> 1;
+    let mut fraction = 0;
+
+    for bit in (0..16).rev() {
+        mantissa = (mantissa*mantissa) >> 62;
+
+        if mantissa >= 2 << 62 {
+            mantissa >>= 1;
+            fraction |= 1 << bit;
+        }
+    }
+
+    (integer << 16) | fraction
+}
+
+/// Natural logarithm of a non-zero `value` in Q16 fixed point
+pub fn ln_q16(value: u64) -> i64 {
+    (log2_q16(value)*LN_2_Q16) >> 16
+}
@@ -0,0 +1,219 @@
+use crate::{error::NtcError, log::ln_q16};
+
+/// Zero degrees celsius in 1/100 kelvin
+const ZERO_CELSIUS: i64 = 27315;
+
+/// Samples within this part of the full scale from either rail are taken
+/// as a broken circuit, e.g. below 101 ohms or above 990 kilohms with
+/// a 10 kilohm series resistor, far outside of the range of the models
+const RAIL_MARGIN_PERCENT: u32 = 1;
+
+/// Position of the thermistor in the voltage divider with the series resistor
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Divider {
+    /// Series resistor to the supply, thermistor to the ground
+    NtcToGround,
+    /// Thermistor to the supply, series resistor to the ground
+    NtcToSupply,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum NtcModel {
+    /// Beta equation, 1/T = 1/T0 + ln(R/R0)/B
+    Beta {
+        beta: u32,
+        /// Resistance at the nominal temperature
+        nominal_ohms: u32,
+        /// In 1/100 degrees celsius, usually 25 degrees
+        nominal_temperature: i32,
+    },
+    /// Steinhart-Hart equation, 1/T = A + B*ln(R) + C*ln(R)^3,
+    /// coefficients are multiplied by 1e15
+    SteinhartHart {
+        a: i64,
+        b: i64,
+        c: i64,
+    },
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct NtcConfig {
+    pub series_ohms: u32,
+    pub divider: Divider,
+    pub model: NtcModel,
+}
+
+/// Thermistor resistance in milliohms from the ADC `sample` with
+/// the given `full_scale` (highest possible sample value)
+pub fn resistance_milliohms(
+    sample: u32,
+    full_scale: u32,
+    config: &NtcConfig,
+) -> Result<u64, NtcError> {
+    let (ntc_part, series_part) = match config.divider {
+        Divider::NtcToGround => (sample, full_scale.saturating_sub(sample)),
+        Divider::NtcToSupply => (full_scale.saturating_sub(sample), sample),
+    };
+
+    // Offset and noise of the ADC keep the samples off the exact rail values
+    let margin = full_scale/100*RAIL_MARGIN_PERCENT;
+
+    if ntc_part <= margin {
+        return Err(NtcError::ShortCircuit);
+    }
+
+    if series_part <= margin {
+        return Err(NtcError::OpenCircuit);
+    }
+
+    Ok(config.series_ohms as u64*1000*ntc_part as u64/series_part as u64)
+}
+
+/// Thermistor temperature in 1/100 degrees celsius
+pub fn temperature_centidegrees(milliohms: u64, model: &NtcModel) -> i32 {
+    // Reciprocal temperature in 1/K multiplied by 1e15
+    let reciprocal: i128 = match *model {
+        NtcModel::Beta { beta, nominal_ohms, nominal_temperature } => {
+            let nominal_kelvin = nominal_temperature as i128 + ZERO_CELSIUS as i128;
+            let ln_ratio = ln_q16(milliohms.max(1)) - ln_q16(nominal_ohms as u64*1000);
+            let ln_ratio = (ln_ratio as i128*1_000_000_000_000_000) >> 16;
+            100_000_000_000_000_000/nominal_kelvin + ln_ratio/beta as i128
+        },
+        NtcModel::SteinhartHart { a, b, c } => {
+            // ln(R) with R in ohms
+            let ln = ln_q16(milliohms.max(1)) as i128 - ln_q16(1000) as i128;
+            a as i128 + ((b as i128*ln) >> 16) + ((c as i128*ln*ln*ln) >> 48)
+        },
+    };
+
+    if reciprocal <= 0 {
+        return i32::MAX;
+    }
+
+    // Temperature in 1/100 kelvin, rounded
+    let centikelvin = (200_000_000_000_000_000/reciprocal + 1)/2;
+    (centikelvin - ZERO_CELSIUS as i128) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resistance table of the YSI 44006 thermistor, 10 kilohms at 25 degrees
+    const TABLE: [(i32, u32); 12] = [
+        (0, 32_650_000),
+        (10, 19_900_000),
+        (20, 12_490_000),
+        (25, 10_000_000),
+        (30, 8_057_000),
+        (40, 5_327_000),
+        (50, 3_603_000),
+        (60, 2_488_000),
+        (70, 1_752_000),
+        (80, 1_258_000),
+        (90, 917_700),
+        (100, 680_000),
+    ];
+
+    /// B25/85 of the table
+    const BETA: NtcModel = NtcModel::Beta {
+        beta: 3969,
+        nominal_ohms: 10_000,
+        nominal_temperature: 2500,
+    };
+
+    /// Coefficients fitted to the table at 0, 25 and 100 degrees
+    const STEINHART_HART: NtcModel = NtcModel::SteinhartHart {
+        a: 1_125_293_571_112,
+        b: 234_714_773_097,
+        c: 85_650_189,
+    };
+
+    const CONFIG: NtcConfig = NtcConfig {
+        series_ohms: 10_000,
+        divider: Divider::NtcToGround,
+        model: STEINHART_HART,
+    };
+
+    const FULL_SCALE: u32 = 4095;
+
+    #[test]
+    fn steinhart_hart_matches_table() {
+        for (celsius, milliohms) in TABLE {
+            let temperature = temperature_centidegrees(milliohms as u64, &STEINHART_HART);
+            assert!((temperature - celsius*100).abs() <= 5, "{} degrees: {}", celsius, temperature);
+        }
+    }
+
+    #[test]
+    fn beta_matches_table() {
+        for (celsius, milliohms) in TABLE {
+            let temperature = temperature_centidegrees(milliohms as u64, &BETA);
+
+            // The beta equation is exact only at 25 and 85 degrees
+            let tolerance = if (25..=85).contains(&celsius) { 30 } else { 70 };
+            assert!(
+                (temperature - celsius*100).abs() <= tolerance,
+                "{} degrees: {}", celsius, temperature
+            );
+        }
+    }
+
+    #[test]
+    fn nominal_temperature() {
+        assert_eq!(temperature_centidegrees(10_000_000, &BETA), 2500);
+        assert!((temperature_centidegrees(10_000_000, &STEINHART_HART) - 2500).abs() <= 1);
+    }
+
+    #[test]
+    fn resistance_of_the_divider() {
+        // Half of the full scale, thermistor equals the series resistor
+        let milliohms = resistance_milliohms(2048, FULL_SCALE, &CONFIG).unwrap();
+        assert!((milliohms as i64 - 10_000_000).abs() <= 5_000, "{}", milliohms);
+
+        let to_supply = NtcConfig { divider: Divider::NtcToSupply, ..CONFIG };
+        assert_eq!(resistance_milliohms(1023, FULL_SCALE, &to_supply), Ok(30_029_325));
+        assert_eq!(resistance_milliohms(3072, FULL_SCALE, &CONFIG), Ok(30_029_325));
+    }
+
+    #[test]
+    fn table_through_the_divider() {
+        for (celsius, milliohms) in TABLE {
+            // Ideal ADC sample for the table resistance
+            let sample = (FULL_SCALE as u64*milliohms as u64 + (milliohms as u64 + 10_000_000)/2)
+                /(milliohms as u64 + 10_000_000);
+
+            let temperature = resistance_milliohms(sample as u32, FULL_SCALE, &CONFIG)
+                .map(|milliohms| temperature_centidegrees(milliohms, &STEINHART_HART))
+                .unwrap();
+
+            // One ADC step is up to 0.3 degrees at the ends of the range
+            assert!((temperature - celsius*100).abs() <= 30, "{} degrees: {}", celsius, temperature);
+        }
+    }
+
+    #[test]
+    fn samples_near_the_rails() {
+        let to_supply = NtcConfig { divider: Divider::NtcToSupply, ..CONFIG };
+
+        for sample in [0, 1, 40] {
+            assert_eq!(resistance_milliohms(sample, FULL_SCALE, &CONFIG), Err(NtcError::ShortCircuit));
+            assert_eq!(resistance_milliohms(sample, FULL_SCALE, &to_supply), Err(NtcError::OpenCircuit));
+        }
+
+        for sample in [FULL_SCALE - 40, FULL_SCALE - 1, FULL_SCALE, FULL_SCALE + 1] {
+            assert_eq!(resistance_milliohms(sample, FULL_SCALE, &CONFIG), Err(NtcError::OpenCircuit));
+            assert_eq!(resistance_milliohms(sample, FULL_SCALE, &to_supply), Err(NtcError::ShortCircuit));
+        }
+
+        assert!(resistance_milliohms(41, FULL_SCALE, &CONFIG).is_ok());
+        assert!(resistance_milliohms(FULL_SCALE - 41, FULL_SCALE, &CONFIG).is_ok());
+    }
+
+    #[test]
+    fn extreme_resistances() {
+        assert!(temperature_centidegrees(0, &BETA) > 10_000);
+        assert!(temperature_centidegrees(u64::MAX, &BETA) < -10_000);
+        assert!(temperature_centidegrees(1, &STEINHART_HART) > 10_000);
+    }
+}
\ No newline at end of file
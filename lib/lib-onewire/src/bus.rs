@@ -0,0 +1,143 @@
+use core::fmt::{Debug, Display};
+use crate::{error::OneWireError, crc::crc8};
+
+pub const READ_ROM: u8 = 0x33;
+pub const MATCH_ROM: u8 = 0x55;
+pub const SKIP_ROM: u8 = 0xCC;
+pub const SEARCH_ROM: u8 = 0xF0;
+
+/// Bit level access to the 1-Wire bus, implemented either by a GPIO pin
+/// with precise timing or by a simulated bus
+pub trait OneWireBus {
+    type Error: Debug;
+
+    /// Send the reset pulse and return whether any device answered
+    /// with the presence pulse
+    fn reset(&mut self) -> Result<bool, Self::Error>;
+
+    fn write_bit(&mut self, bit: bool) -> Result<(), Self::Error>;
+
+    fn read_bit(&mut self) -> Result<bool, Self::Error>;
+}
+
+/// 64-bit device ROM code - family code, 48-bit serial number and CRC
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Address(pub [u8; 8]);
+
+impl Address {
+    pub fn family_code(&self) -> u8 {
+        self.0[0]
+    }
+
+    /// Check the CRC, all zero ROM code (bus stuck low) is refused as well
+    pub fn is_valid(&self) -> bool {
+        crc8(&self.0) == 0 && self.0 != [0; 8]
+    }
+}
+
+impl Display for Address {
+    /// Print the ROM code the way it is transferred over the bus
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{:02X}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Byte level 1-Wire protocol on top of the bit level bus
+pub struct OneWire<B> {
+    bus: B,
+}
+
+impl<B, E> OneWire<B>
+where B: OneWireBus<Error = E>, E: Debug {
+    pub fn new(bus: B) -> Self {
+        Self { bus }
+    }
+
+    pub fn destroy(self) -> B {
+        self.bus
+    }
+
+    /// Reset the bus and fail if there is no device present
+    pub fn reset(&mut self) -> Result<(), OneWireError<E>> {
+        match self.bus.reset().map_err(OneWireError::Bus)? {
+            true => Ok(()),
+            false => Err(OneWireError::NoPresence),
+        }
+    }
+
+    /// Reset the bus and address a single device (or all devices if
+    /// `address` is `None`), a function command is expected next
+    pub fn select(&mut self, address: Option<&Address>) -> Result<(), OneWireError<E>> {
+        self.reset()?;
+
+        match address {
+            Some(address) => {
+                self.write_byte(MATCH_ROM)?;
+                self.write_bytes(&address.0)
+            },
+            None => self.write_byte(SKIP_ROM),
+        }
+    }
+
+    /// Read the ROM code, works only if there is just a single device on the bus
+    pub fn read_address(&mut self) -> Result<Address, OneWireError<E>> {
+        self.reset()?;
+        self.write_byte(READ_ROM)?;
+        let mut address = Address([0; 8]);
+        self.read_bytes(&mut address.0)?;
+
+        match address.is_valid() {
+            true => Ok(address),
+            false => Err(OneWireError::CrcMismatch),
+        }
+    }
+
+    pub fn write_byte(&mut self, byte: u8) -> Result<(), OneWireError<E>> {
+        // Least significant bit goes first
+        for bit in 0..8 {
+            self.write_bit(byte & (1 << bit) != 0)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), OneWireError<E>> {
+        for byte in bytes.iter() {
+            self.write_byte(*byte)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read_byte(&mut self) -> Result<u8, OneWireError<E>> {
+        let mut byte = 0;
+
+        for bit in 0..8 {
+            if self.read_bit()? {
+                byte |= 1 << bit;
+            }
+        }
+
+        Ok(byte)
+    }
+
+    pub fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), OneWireError<E>> {
+        for byte in bytes.iter_mut() {
+            *byte = self.read_byte()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), OneWireError<E>> {
+        self.bus.write_bit(bit).map_err(OneWireError::Bus)
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool, OneWireError<E>> {
+        self.bus.read_bit().map_err(OneWireError::Bus)
+    }
+}
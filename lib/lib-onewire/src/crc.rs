@@ -0,0 +1,48 @@
+/// Dallas/Maxim CRC-8 (polynomial x^8 + x^5 + x^4 + 1) used for ROM codes
+/// and scratchpad contents, data followed by its CRC yields zero
+pub fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |crc, byte| {
+        let mut crc = crc ^ byte;
+
+        for _ in 0..8 {
+            crc = if crc & 0x01 != 0 { (crc >> 1) ^ 0x8C } else { crc >> 1 };
+        }
+
+        crc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rom_code_example() {
+        // Maxim application note 27
+        let rom = [0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00, 0xA2];
+        assert_eq!(crc8(&rom[..7]), 0xA2);
+        assert_eq!(crc8(&rom), 0);
+    }
+
+    #[test]
+    fn power_on_scratchpad() {
+        let scratchpad = [0x50, 0x05, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0x1C];
+        assert_eq!(crc8(&scratchpad), 0);
+    }
+
+    #[test]
+    fn detects_single_bit_errors() {
+        let rom = [0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00, 0xA2];
+
+        for bit in 0..64 {
+            let mut corrupted = rom;
+            corrupted[bit/8] ^= 1 << (bit % 8);
+            assert_ne!(crc8(&corrupted), 0, "bit {}", bit);
+        }
+    }
+
+    #[test]
+    fn empty_data() {
+        assert_eq!(crc8(&[]), 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,233 @@
+use core::fmt::Debug;
+use crate::{bus::{OneWire, OneWireBus, Address}, error::OneWireError, crc::crc8};
+
+pub const FAMILY_CODE: u8 = 0x28;
+
+pub(crate) const CONVERT_T: u8 = 0x44;
+pub(crate) const WRITE_SCRATCHPAD: u8 = 0x4E;
+pub(crate) const READ_SCRATCHPAD: u8 = 0xBE;
+
+/// Temperature register after power-on reset, 85 degrees
+const POWER_ON_VALUE: i16 = 0x0550;
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub enum Resolution {
+    /// 0.5 degree steps, 94 ms conversion
+    Bits9,
+    /// 0.25 degree steps, 188 ms conversion
+    Bits10,
+    /// 0.125 degree steps, 375 ms conversion
+    Bits11,
+    /// 0.0625 degree steps, 750 ms conversion
+    Bits12,
+}
+
+impl Resolution {
+    pub fn conversion_time_ms(&self) -> u16 {
+        match self {
+            Resolution::Bits9 => 94,
+            Resolution::Bits10 => 188,
+            Resolution::Bits11 => 375,
+            Resolution::Bits12 => 750,
+        }
+    }
+
+    fn config_register(&self) -> u8 {
+        match self {
+            Resolution::Bits9 => 0x1F,
+            Resolution::Bits10 => 0x3F,
+            Resolution::Bits11 => 0x5F,
+            Resolution::Bits12 => 0x7F,
+        }
+    }
+
+    /// Bits of the temperature register that are undefined in this resolution
+    fn undefined_bits(&self) -> i16 {
+        match self {
+            Resolution::Bits9 => 0b111,
+            Resolution::Bits10 => 0b11,
+            Resolution::Bits11 => 0b1,
+            Resolution::Bits12 => 0,
+        }
+    }
+}
+
+/// Set conversion resolution of the device with `address` (or all devices
+/// on the bus), alarm thresholds are left at zero as alarm search is not used
+pub fn set_resolution<B, E>(
+    bus: &mut OneWire<B>,
+    address: Option<&Address>,
+    resolution: Resolution,
+) -> Result<(), OneWireError<E>>
+where B: OneWireBus<Error = E>, E: Debug {
+    bus.select(address)?;
+    bus.write_byte(WRITE_SCRATCHPAD)?;
+    bus.write_bytes(&[0, 0, resolution.config_register()])
+}
+
+/// Start temperature conversion on the device with `address` (or all
+/// devices on the bus), the result can be read after the conversion time
+pub fn start_conversion<B, E>(
+    bus: &mut OneWire<B>,
+    address: Option<&Address>,
+) -> Result<(), OneWireError<E>>
+where B: OneWireBus<Error = E>, E: Debug {
+    bus.select(address)?;
+    bus.write_byte(CONVERT_T)
+}
+
+/// Read result of the last conversion in 1/100 degrees celsius
+pub fn read_temperature<B, E>(
+    bus: &mut OneWire<B>,
+    address: &Address,
+    resolution: Resolution,
+) -> Result<i32, OneWireError<E>>
+where B: OneWireBus<Error = E>, E: Debug {
+    let mut scratchpad = [0; 9];
+    bus.select(Some(address))?;
+    bus.write_byte(READ_SCRATCHPAD)?;
+    bus.read_bytes(&mut scratchpad)?;
+
+    // All zero scratchpad has a valid CRC, it is read when the bus is stuck low
+    if crc8(&scratchpad) != 0 || scratchpad == [0; 9] {
+        return Err(OneWireError::CrcMismatch);
+    }
+
+    let raw = i16::from_le_bytes([scratchpad[0], scratchpad[1]]);
+
+    if raw == POWER_ON_VALUE {
+        return Err(OneWireError::NotConverted);
+    }
+
+    Ok(raw_to_centidegrees(raw & !resolution.undefined_bits()))
+}
+
+/// Convert register value in 1/16 degree steps to 1/100 degrees celsius
+pub fn raw_to_centidegrees(raw: i16) -> i32 {
+    raw as i32*100/16
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec;
+    use super::*;
+    use crate::simulated::{SimulatedBus, Device};
+
+    const SERIAL: [u8; 6] = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+
+    fn convert(raw: i16, resolution: Resolution) -> Result<i32, OneWireError<()>> {
+        let device = Device::new(FAMILY_CODE, SERIAL, raw);
+        let address = Address(device.rom);
+        let mut bus = OneWire::new(SimulatedBus::new(vec![device]));
+
+        set_resolution(&mut bus, None, resolution)?;
+        start_conversion(&mut bus, None)?;
+        read_temperature(&mut bus, &address, resolution)
+    }
+
+    #[test]
+    fn datasheet_temperatures() {
+        // Temperature/data relationship table of the datasheet
+        let table = [
+            (0x07D0_u16, 12500),
+            (0x0191, 2506),
+            (0x00A2, 1012),
+            (0x0008, 50),
+            (0x0000, 0),
+            (0xFFF8, -50),
+            (0xFF5E, -1012),
+            (0xFE6F, -2506),
+            (0xFC90, -5500),
+        ];
+
+        for (raw, centidegrees) in table {
+            assert_eq!(raw_to_centidegrees(raw as i16), centidegrees, "raw {:04X}", raw);
+            assert!(convert(raw as i16, Resolution::Bits12).ok() == Some(centidegrees), "raw {:04X}", raw);
+        }
+    }
+
+    #[test]
+    fn undefined_bits_are_ignored() {
+        assert!(convert(0x0197, Resolution::Bits9).ok() == Some(2500));
+        assert!(convert(0x0197, Resolution::Bits10).ok() == Some(2525));
+        assert!(convert(0x0197, Resolution::Bits11).ok() == Some(2537));
+        assert!(convert(0x0197, Resolution::Bits12).ok() == Some(2543));
+    }
+
+    #[test]
+    fn resolution_is_written_to_config_register() {
+        let device = Device::new(FAMILY_CODE, SERIAL, 0);
+        let mut bus = OneWire::new(SimulatedBus::new(vec![device]));
+
+        assert!(set_resolution(&mut bus, None, Resolution::Bits10).is_ok());
+
+        let bus = bus.destroy();
+        assert_eq!(bus.devices[0].scratchpad[2..5], [0, 0, 0x3F]);
+        assert_eq!(crc8(&bus.devices[0].scratchpad), 0);
+    }
+
+    #[test]
+    fn selected_device_converts() {
+        let first = Device::new(FAMILY_CODE, [0x01, 0, 0, 0, 0, 0], 0x0191);
+        let second = Device::new(FAMILY_CODE, [0x02, 0, 0, 0, 0, 0], -162);
+        let (first_address, second_address) = (Address(first.rom), Address(second.rom));
+        let mut bus = OneWire::new(SimulatedBus::new(vec![first, second]));
+
+        assert!(start_conversion(&mut bus, Some(&second_address)).is_ok());
+
+        let first = read_temperature(&mut bus, &first_address, Resolution::Bits12);
+        let second = read_temperature(&mut bus, &second_address, Resolution::Bits12);
+
+        assert!(matches!(first, Err(OneWireError::NotConverted)));
+        assert!(second.ok() == Some(-1012));
+    }
+
+    #[test]
+    fn power_on_value_is_rejected() {
+        let device = Device::new(FAMILY_CODE, SERIAL, 0x0191);
+        let address = Address(device.rom);
+        let mut bus = OneWire::new(SimulatedBus::new(vec![device]));
+
+        // Conversion was never started
+        let result = read_temperature(&mut bus, &address, Resolution::Bits12);
+
+        assert!(matches!(result, Err(OneWireError::NotConverted)));
+    }
+
+    #[test]
+    fn corrupted_scratchpad_is_rejected() {
+        let mut device = Device::new(FAMILY_CODE, SERIAL, 0x0191);
+        device.scratchpad[4] ^= 0x20;
+        let address = Address(device.rom);
+        let mut bus = OneWire::new(SimulatedBus::new(vec![device]));
+
+        let result = read_temperature(&mut bus, &address, Resolution::Bits12);
+
+        assert!(matches!(result, Err(OneWireError::CrcMismatch)));
+    }
+
+    #[test]
+    fn all_zero_scratchpad_is_rejected() {
+        let device = Device::new(FAMILY_CODE, SERIAL, 0x0191);
+        let address = Address(device.rom);
+        let mut bus = SimulatedBus::new(vec![device]);
+        bus.stuck_low = true;
+        let mut bus = OneWire::new(bus);
+
+        let result = read_temperature(&mut bus, &address, Resolution::Bits12);
+
+        assert!(matches!(result, Err(OneWireError::CrcMismatch)));
+    }
+
+    #[test]
+    fn missing_device() {
+        let address = Address(Device::new(FAMILY_CODE, SERIAL, 0).rom);
+        let mut bus = OneWire::new(SimulatedBus::new(vec![]));
+
+        let result = read_temperature(&mut bus, &address, Resolution::Bits12);
+
+        assert!(matches!(result, Err(OneWireError::NoPresence)));
+    }
+}
\ No newline at end of file
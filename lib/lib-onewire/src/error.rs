@@ -0,0 +1,23 @@
+use core::fmt::{Debug, Display};
+
+pub enum OneWireError<E>
+where E: Debug {
+    /// No device answered the reset pulse
+    NoPresence,
+    CrcMismatch,
+    /// Temperature register still holds its power-on value, the conversion
+    /// did not run, e.g. parasite power was lost
+    NotConverted,
+    Bus(E),
+}
+
+impl<E> Display for OneWireError<E> where E: Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OneWireError::NoPresence => write!(f, "NoPres"),
+            OneWireError::CrcMismatch => write!(f, "Crc"),
+            OneWireError::NotConverted => write!(f, "NoConv"),
+            OneWireError::Bus(ref err) => write!(f, "Bus:{:?}", err),
+        }
+    }
+}
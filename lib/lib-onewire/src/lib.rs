@@ -0,0 +1,14 @@
+#![no_std]
+
+mod error;
+mod crc;
+mod bus;
+mod search;
+pub mod ds18b20;
+#[cfg(test)]
+mod simulated;
+
+pub use error::OneWireError;
+pub use crc::crc8;
+pub use bus::{OneWireBus, OneWire, Address};
+pub use search::DeviceSearch;
@@ -0,0 +1,180 @@
+use core::fmt::Debug;
+use crate::{bus::{OneWire, OneWireBus, Address, SEARCH_ROM}, error::OneWireError};
+
+/// State of the ROM search algorithm (Maxim application note 187),
+/// every call to `next` discovers one more device on the bus
+#[derive(Default)]
+pub struct DeviceSearch {
+    rom: [u8; 8],
+    /// Bit position (1 - 64) where the zero path was taken last time
+    last_discrepancy: u8,
+    finished: bool,
+}
+
+impl DeviceSearch {
+    /// Return address of the next device, or `None` if all devices
+    /// have already been found
+    pub fn next<B, E>(
+        &mut self,
+        bus: &mut OneWire<B>,
+    ) -> Result<Option<Address>, OneWireError<E>>
+    where B: OneWireBus<Error = E>, E: Debug {
+        if self.finished {
+            return Ok(None);
+        }
+
+        match bus.reset() {
+            Ok(()) => {},
+            Err(OneWireError::NoPresence) => {
+                self.finished = true;
+                return Ok(None);
+            },
+            Err(error) => return Err(error),
+        }
+
+        bus.write_byte(SEARCH_ROM)?;
+        let mut last_zero = 0;
+
+        for bit_number in 1..=64_u8 {
+            let index = ((bit_number - 1)/8) as usize;
+            let mask = 1 << ((bit_number - 1) % 8);
+
+            // Every device sends its address bit followed by its complement
+            let id_bit = bus.read_bit()?;
+            let complement_bit = bus.read_bit()?;
+
+            let direction = match (id_bit, complement_bit) {
+                (true, true) => {
+                    // Nobody participates in the search anymore
+                    self.finished = true;
+                    return Ok(None);
+                },
+                (false, true) => false,
+                (true, false) => true,
+                (false, false) => {
+                    // Discrepancy, devices with both 0 and 1 at this position
+                    let direction = if bit_number < self.last_discrepancy {
+                        self.rom[index] & mask != 0
+                    } else {
+                        bit_number == self.last_discrepancy
+                    };
+
+                    if !direction {
+                        last_zero = bit_number;
+                    }
+
+                    direction
+                },
+            };
+
+            if direction {
+                self.rom[index] |= mask;
+            } else {
+                self.rom[index] &= !mask;
+            }
+
+            bus.write_bit(direction)?;
+        }
+
+        self.last_discrepancy = last_zero;
+        self.finished = last_zero == 0;
+        let address = Address(self.rom);
+
+        match address.is_valid() {
+            true => Ok(Some(address)),
+            false => {
+                self.finished = true;
+                Err(OneWireError::CrcMismatch)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{vec, vec::Vec};
+    use super::*;
+    use crate::simulated::{SimulatedBus, Device};
+
+    fn search_all(bus: &mut OneWire<SimulatedBus>) -> Vec<Address> {
+        let mut search = DeviceSearch::default();
+        let mut addresses = Vec::new();
+
+        while let Some(address) = search.next(bus).ok().flatten() {
+            addresses.push(address);
+            assert!(addresses.len() <= 8, "search does not terminate");
+        }
+
+        addresses
+    }
+
+    fn roms(devices: &[Device]) -> Vec<[u8; 8]> {
+        let mut roms: Vec<_> = devices.iter().map(|device| device.rom).collect();
+        roms.sort();
+        roms
+    }
+
+    #[test]
+    fn finds_every_device_once() {
+        let devices = vec![
+            Device::new(0x28, [0x01, 0x00, 0x00, 0x00, 0x00, 0x00], 0),
+            Device::new(0x28, [0x02, 0x00, 0x00, 0x00, 0x00, 0x00], 0),
+            Device::new(0x28, [0x01, 0x00, 0x00, 0x00, 0x00, 0x80], 0),
+            Device::new(0x10, [0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55], 0),
+            Device::new(0x28, [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F], 0),
+        ];
+        let expected = roms(&devices);
+        let mut bus = OneWire::new(SimulatedBus::new(devices));
+
+        let mut found: Vec<_> = search_all(&mut bus).iter().map(|address| address.0).collect();
+        found.sort();
+
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn single_device() {
+        let device = Device::new(0x28, [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC], 0);
+        let rom = device.rom;
+        let mut bus = OneWire::new(SimulatedBus::new(vec![device]));
+
+        let found = search_all(&mut bus);
+
+        assert!(found.len() == 1 && found[0].0 == rom);
+        assert!(bus.read_address().ok().map(|address| address.0) == Some(rom));
+    }
+
+    #[test]
+    fn empty_bus() {
+        let mut bus = OneWire::new(SimulatedBus::new(Vec::new()));
+        let mut search = DeviceSearch::default();
+
+        assert!(matches!(search.next(&mut bus), Ok(None)));
+        assert!(matches!(bus.read_address(), Err(OneWireError::NoPresence)));
+    }
+
+    #[test]
+    fn corrupted_rom_code() {
+        let mut device = Device::new(0x28, [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC], 0);
+        device.rom[7] ^= 0x01;
+        let mut bus = OneWire::new(SimulatedBus::new(vec![device]));
+        let mut search = DeviceSearch::default();
+
+        assert!(matches!(search.next(&mut bus), Err(OneWireError::CrcMismatch)));
+        assert!(matches!(search.next(&mut bus), Ok(None)));
+    }
+
+    #[test]
+    fn bus_stuck_low() {
+        let mut bus = SimulatedBus::new(Vec::new());
+        bus.stuck_low = true;
+        let mut bus = OneWire::new(bus);
+        let mut search = DeviceSearch::default();
+
+        // All zero ROM code has a valid CRC
+        assert!(matches!(search.next(&mut bus), Err(OneWireError::CrcMismatch)));
+        assert!(matches!(bus.read_address(), Err(OneWireError::CrcMismatch)));
+    }
+}
\ No newline at end of file
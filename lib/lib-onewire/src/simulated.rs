@@ -0,0 +1,231 @@
+extern crate std;
+
+use std::{vec, vec::Vec, collections::VecDeque};
+use crate::{
+    bus::{READ_ROM, MATCH_ROM, SKIP_ROM, SEARCH_ROM},
+    ds18b20::{CONVERT_T, WRITE_SCRATCHPAD, READ_SCRATCHPAD},
+    crc::crc8,
+};
+
+/// Scratchpad of DS18B20 after power-on, 85 degrees and 12 bit resolution
+const POWER_ON_SCRATCHPAD: [u8; 8] = [0x50, 0x05, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10];
+
+/// Simulated DS18B20 on the bus
+pub struct Device {
+    pub rom: [u8; 8],
+    pub scratchpad: [u8; 9],
+    /// Raw value stored into the scratchpad by the conversion
+    pub temperature: i16,
+}
+
+impl Device {
+    /// Device with a valid ROM code built from the family code and serial number
+    pub fn new(family_code: u8, serial: [u8; 6], temperature: i16) -> Self {
+        let mut rom = [family_code, 0, 0, 0, 0, 0, 0, 0];
+        rom[1..7].copy_from_slice(&serial);
+        rom[7] = crc8(&rom[..7]);
+
+        let mut device = Self { rom, scratchpad: [0; 9], temperature };
+        device.scratchpad[..8].copy_from_slice(&POWER_ON_SCRATCHPAD);
+        device.update_crc();
+        device
+    }
+
+    fn update_crc(&mut self) {
+        self.scratchpad[8] = crc8(&self.scratchpad[..8]);
+    }
+}
+
+enum State {
+    /// Bus was not reset yet or the command is not supported
+    Idle,
+    RomCommand,
+    MatchRom,
+    /// Address bit, its complement and the direction of every position
+    SearchRom { bit: usize, reads: u8 },
+    FunctionCommand,
+    WriteScratchpad,
+}
+
+/// Bit level simulation of devices sharing an open drain bus, a bit read
+/// is the wired AND of all devices transmitting
+pub struct SimulatedBus {
+    pub devices: Vec<Device>,
+    /// Data line shorted to the ground
+    pub stuck_low: bool,
+    state: State,
+    /// Devices addressed by the ROM command
+    selected: Vec<bool>,
+    written: Vec<bool>,
+    transmitting: VecDeque<bool>,
+}
+
+impl SimulatedBus {
+    pub fn new(devices: Vec<Device>) -> Self {
+        Self {
+            selected: vec![false; devices.len()],
+            devices,
+            stuck_low: false,
+            state: State::Idle,
+            written: Vec::new(),
+            transmitting: VecDeque::new(),
+        }
+    }
+
+    fn written_bytes(&self) -> Vec<u8> {
+        self.written.chunks(8)
+            .map(|bits| bits.iter().rev().fold(0, |byte, bit| (byte << 1) | *bit as u8))
+            .collect()
+    }
+
+    /// Wired AND of the bytes sent by the selected devices, least significant bit first
+    fn transmit(&mut self, data: impl Fn(&Device) -> Vec<u8>) {
+        let mut bytes: Option<Vec<u8>> = None;
+
+        for device in self.selected_devices() {
+            let sent = data(device);
+
+            bytes = Some(match bytes {
+                Some(bytes) => bytes.iter().zip(sent.iter()).map(|(a, b)| a & b).collect(),
+                None => sent,
+            });
+        }
+
+        for byte in bytes.unwrap_or_default() {
+            self.transmitting.extend((0..8).map(|bit| byte & (1 << bit) != 0));
+        }
+    }
+
+    fn selected_devices(&mut self) -> impl Iterator<Item = &mut Device> {
+        self.devices.iter_mut().zip(self.selected.iter())
+            .filter(|(_, selected)| **selected)
+            .map(|(device, _)| device)
+    }
+
+    fn rom_bit(device: &Device, bit: usize) -> bool {
+        device.rom[bit/8] & (1 << (bit % 8)) != 0
+    }
+
+    fn on_command(&mut self) {
+        match self.state {
+            State::RomCommand if self.written.len() == 8 => {
+                let command = self.written_bytes()[0];
+                self.written.clear();
+
+                self.state = match command {
+                    READ_ROM => {
+                        self.selected.fill(true);
+                        self.transmit(|device| device.rom.to_vec());
+                        State::Idle
+                    },
+                    MATCH_ROM => State::MatchRom,
+                    SKIP_ROM => {
+                        self.selected.fill(true);
+                        State::FunctionCommand
+                    },
+                    SEARCH_ROM => {
+                        self.selected.fill(true);
+                        State::SearchRom { bit: 0, reads: 0 }
+                    },
+                    _ => State::Idle,
+                };
+            },
+            State::MatchRom if self.written.len() == 64 => {
+                let address = self.written_bytes();
+                self.written.clear();
+
+                for (device, selected) in self.devices.iter().zip(self.selected.iter_mut()) {
+                    *selected = device.rom[..] == address[..];
+                }
+
+                self.state = State::FunctionCommand;
+            },
+            State::FunctionCommand if self.written.len() == 8 => {
+                let command = self.written_bytes()[0];
+                self.written.clear();
+
+                self.state = match command {
+                    READ_SCRATCHPAD => {
+                        self.transmit(|device| device.scratchpad.to_vec());
+                        State::Idle
+                    },
+                    CONVERT_T => {
+                        for device in self.selected_devices() {
+                            device.scratchpad[..2].copy_from_slice(&device.temperature.to_le_bytes());
+                            device.update_crc();
+                        }
+
+                        State::Idle
+                    },
+                    WRITE_SCRATCHPAD => State::WriteScratchpad,
+                    _ => State::Idle,
+                };
+            },
+            State::WriteScratchpad if self.written.len() == 24 => {
+                let bytes = self.written_bytes();
+                self.written.clear();
+
+                for device in self.selected_devices() {
+                    device.scratchpad[2..5].copy_from_slice(&bytes);
+                    device.update_crc();
+                }
+
+                self.state = State::Idle;
+            },
+            _ => {},
+        }
+    }
+}
+
+impl crate::OneWireBus for SimulatedBus {
+    type Error = ();
+
+    fn reset(&mut self) -> Result<bool, ()> {
+        self.state = State::RomCommand;
+        self.written.clear();
+        self.transmitting.clear();
+        // Line held low looks like a presence pulse
+        Ok(self.stuck_low || !self.devices.is_empty())
+    }
+
+    fn write_bit(&mut self, bit: bool) -> Result<(), ()> {
+        if let State::SearchRom { bit: position, reads: 2 } = self.state {
+            // Devices with the other bit at this position stop participating
+            for (device, selected) in self.devices.iter().zip(self.selected.iter_mut()) {
+                *selected &= Self::rom_bit(device, position) == bit;
+            }
+
+            self.state = match position {
+                63 => State::Idle,
+                _ => State::SearchRom { bit: position + 1, reads: 0 },
+            };
+
+            return Ok(());
+        }
+
+        self.written.push(bit);
+        self.on_command();
+        Ok(())
+    }
+
+    fn read_bit(&mut self) -> Result<bool, ()> {
+        if self.stuck_low {
+            return Ok(false);
+        }
+
+        if let State::SearchRom { bit, ref mut reads } = self.state {
+            let complement = *reads == 1;
+            *reads += 1;
+
+            // Every participating device sends the bit and then its complement
+            let value = self.devices.iter().zip(self.selected.iter())
+                .filter(|(_, selected)| **selected)
+                .all(|(device, _)| Self::rom_bit(device, bit) != complement);
+
+            return Ok(value);
+        }
+
+        // Released bus is pulled high
+        Ok(self.transmitting.pop_front().unwrap_or(true))
+    }
+}
\ No newline at end of file
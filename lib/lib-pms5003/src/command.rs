@@ -0,0 +1,32 @@
+/// Commands accepted by the sensor over UART
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Command {
+    /// Request a single data frame in passive mode
+    ReadPassive,
+    /// Send data frames only on request
+    PassiveMode,
+    /// Send data frames continuously (default after power up)
+    ActiveMode,
+    Sleep,
+    Wakeup,
+}
+
+impl Command {
+    /// Bytes to be sent to the sensor, including the start characters
+    /// and the checksum
+    pub fn frame(self) -> [u8; 7] {
+        let (command, data) = match self {
+            Command::ReadPassive => (0xE2, 0x00),
+            Command::PassiveMode => (0xE1, 0x00),
+            Command::ActiveMode => (0xE1, 0x01),
+            Command::Sleep => (0xE4, 0x00),
+            Command::Wakeup => (0xE4, 0x01),
+        };
+
+        let frame = [0x42, 0x4D, command, 0x00, data];
+        let sum = frame.iter().fold(0_u16, |sum, &byte| sum + byte as u16);
+        let [sum_msb, sum_lsb] = sum.to_be_bytes();
+
+        [frame[0], frame[1], frame[2], frame[3], frame[4], sum_msb, sum_lsb]
+    }
+}
\ No newline at end of file
@@ -0,0 +1,18 @@
+use core::fmt::Display;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum FrameError {
+    /// Frame length field is not one of the lengths sent by the sensor
+    InvalidLength,
+    /// Sum of the frame bytes does not match the checksum field
+    ChecksumMismatch,
+}
+
+impl Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FrameError::InvalidLength => write!(f, "LEN"),
+            FrameError::ChecksumMismatch => write!(f, "CRC"),
+        }
+    }
+}
\ No newline at end of file
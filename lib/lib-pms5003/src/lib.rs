@@ -0,0 +1,9 @@
+#![no_std]
+
+mod error;
+mod parser;
+mod command;
+
+pub use error::FrameError;
+pub use parser::{Parser, Measurement, FRAME_LENGTH};
+pub use command::Command;
\ No newline at end of file
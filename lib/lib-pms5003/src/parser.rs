@@ -0,0 +1,365 @@
+use crate::error::FrameError;
+
+const START_CHARACTERS: [u8; 2] = [0x42, 0x4D];
+
+/// Length field of the data frame with measured values
+const DATA_FRAME_LENGTH: u16 = 28;
+/// Length field of the frame confirming a mode change command
+const RESPONSE_FRAME_LENGTH: u16 = 4;
+
+/// Number of bytes of the longest (data) frame
+pub const FRAME_LENGTH: usize = 4 + DATA_FRAME_LENGTH as usize;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Measurement {
+    /// PM1.0 in ug/m3, CF=1 (factory calibration particles)
+    pub pm1_0_standard: u16,
+    /// PM2.5 in ug/m3, CF=1 (factory calibration particles)
+    pub pm2_5_standard: u16,
+    /// PM10 in ug/m3, CF=1 (factory calibration particles)
+    pub pm10_standard: u16,
+    /// PM1.0 in ug/m3 under atmospheric environment
+    pub pm1_0: u16,
+    /// PM2.5 in ug/m3 under atmospheric environment
+    pub pm2_5: u16,
+    /// PM10 in ug/m3 under atmospheric environment
+    pub pm10: u16,
+    /// Number of particles beyond 0.3, 0.5, 1.0, 2.5, 5.0 and 10 um
+    /// in 0.1 litre of air
+    pub particles: [u16; 6],
+}
+
+/// Byte by byte parser of the frames sent by the sensor, garbage
+/// between frames is skipped and the parser resynchronizes on the next
+/// start characters after a corrupted frame
+pub struct Parser {
+    buffer: [u8; FRAME_LENGTH],
+    position: usize,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self {
+            buffer: [0; FRAME_LENGTH],
+            position: 0,
+        }
+    }
+
+    /// Feed a single received byte, a result is returned when a data frame
+    /// is complete or a corrupted frame is dropped. Confirmations of mode
+    /// change commands are validated, but not returned.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Measurement, FrameError>> {
+        match self.position {
+            0 if byte != START_CHARACTERS[0] => return None,
+            1 if byte != START_CHARACTERS[1] => {
+                // The byte may be the first start character itself
+                self.position = (byte == START_CHARACTERS[0]) as usize;
+                return None;
+            },
+            _ => {},
+        }
+
+        self.buffer[self.position] = byte;
+        self.position += 1;
+
+        if self.position < 4 {
+            return None;
+        }
+
+        let length = u16::from_be_bytes([self.buffer[2], self.buffer[3]]);
+
+        if length != DATA_FRAME_LENGTH && length != RESPONSE_FRAME_LENGTH {
+            self.resynchronize();
+            return Some(Err(FrameError::InvalidLength));
+        }
+
+        if self.position < 4 + length as usize {
+            return None;
+        }
+
+        let (data, checksum) = self.buffer[..self.position].split_at(self.position - 2);
+        let sum = data.iter().fold(0_u16, |sum, &byte| sum.wrapping_add(byte as u16));
+
+        if sum != u16::from_be_bytes([checksum[0], checksum[1]]) {
+            self.resynchronize();
+            return Some(Err(FrameError::ChecksumMismatch));
+        }
+
+        self.position = 0;
+
+        match length {
+            DATA_FRAME_LENGTH => Some(Ok(decode(&self.buffer))),
+            _ => None,
+        }
+    }
+
+    /// Drop the start characters of the corrupted frame and parse the rest
+    /// of the buffered bytes again, a valid frame may start among them
+    fn resynchronize(&mut self) {
+        let buffer = self.buffer;
+        let length = self.position;
+        self.position = 0;
+
+        for &byte in buffer[1..length].iter() {
+            // Errors were already reported for the dropped frame and there
+            // are too few bytes left to complete a data frame
+            let _ = self.push(byte);
+        }
+    }
+}
+
+fn decode(frame: &[u8; FRAME_LENGTH]) -> Measurement {
+    let word = |index: usize| u16::from_be_bytes([frame[4 + 2*index], frame[5 + 2*index]]);
+
+    Measurement {
+        pm1_0_standard: word(0),
+        pm2_5_standard: word(1),
+        pm10_standard: word(2),
+        pm1_0: word(3),
+        pm2_5: word(4),
+        pm10: word(5),
+        particles: core::array::from_fn(|index| word(6 + index)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Data frame as sent by the sensor carrying the measurement
+    fn data_frame(measurement: &Measurement) -> [u8; FRAME_LENGTH] {
+        let mut frame = [0; FRAME_LENGTH];
+        frame[..2].copy_from_slice(&START_CHARACTERS);
+        frame[2..4].copy_from_slice(&DATA_FRAME_LENGTH.to_be_bytes());
+
+        let words = [
+            measurement.pm1_0_standard, measurement.pm2_5_standard, measurement.pm10_standard,
+            measurement.pm1_0, measurement.pm2_5, measurement.pm10,
+        ];
+
+        for (index, word) in words.iter().chain(measurement.particles.iter()).enumerate() {
+            frame[4 + 2*index..6 + 2*index].copy_from_slice(&word.to_be_bytes());
+        }
+
+        // Version and error code
+        frame[28] = 0x97;
+        frame[29] = 0x00;
+
+        let sum = frame[..30].iter().fold(0_u16, |sum, &byte| sum + byte as u16);
+        frame[30..].copy_from_slice(&sum.to_be_bytes());
+        frame
+    }
+
+    fn measurement(seed: u16) -> Measurement {
+        Measurement {
+            pm1_0_standard: seed,
+            pm2_5_standard: seed + 1,
+            pm10_standard: seed + 2,
+            pm1_0: seed + 3,
+            pm2_5: seed + 4,
+            pm10: seed + 5,
+            particles: [seed*100, seed*50, seed*10, seed*5, seed + 1, seed],
+        }
+    }
+
+    type Results = [Option<Result<Measurement, FrameError>>; 64];
+
+    /// Feed all bytes and collect the results
+    fn parse(parser: &mut Parser, bytes: &[u8], results: &mut Results) -> usize {
+        let mut count = 0;
+
+        for &byte in bytes {
+            if let Some(result) = parser.push(byte) {
+                results[count] = Some(result);
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    #[test]
+    fn valid_frame() {
+        let expected = measurement(12);
+        let frame = data_frame(&expected);
+        let mut parser = Parser::new();
+
+        for &byte in frame[..FRAME_LENGTH - 1].iter() {
+            assert_eq!(parser.push(byte), None);
+        }
+
+        assert_eq!(parser.push(frame[FRAME_LENGTH - 1]), Some(Ok(expected)));
+    }
+
+    #[test]
+    fn consecutive_frames() {
+        let mut parser = Parser::new();
+        let mut results = [None; 64];
+
+        for seed in 0..3 {
+            let count = parse(&mut parser, &data_frame(&measurement(seed)), &mut results);
+            assert_eq!((count, results[0]), (1, Some(Ok(measurement(seed)))));
+        }
+    }
+
+    #[test]
+    fn command_response_is_not_returned() {
+        let mut parser = Parser::new();
+        let mut results = [None; 64];
+
+        // Confirmation of the passive mode command
+        let response = [0x42, 0x4D, 0x00, 0x04, 0xE1, 0x00, 0x01, 0x74];
+        assert_eq!(parse(&mut parser, &response, &mut results), 0);
+
+        let count = parse(&mut parser, &data_frame(&measurement(5)), &mut results);
+        assert_eq!((count, results[0]), (1, Some(Ok(measurement(5)))));
+    }
+
+    #[test]
+    fn bad_checksum() {
+        let mut frame = data_frame(&measurement(7));
+        frame[10] ^= 0x01;
+        let mut parser = Parser::new();
+        let mut results = [None; 64];
+
+        let count = parse(&mut parser, &frame, &mut results);
+        assert_eq!((count, results[0]), (1, Some(Err(FrameError::ChecksumMismatch))));
+
+        // Parser is ready for the next frame
+        let count = parse(&mut parser, &data_frame(&measurement(8)), &mut results);
+        assert_eq!((count, results[0]), (1, Some(Ok(measurement(8)))));
+    }
+
+    #[test]
+    fn wrong_length() {
+        let mut parser = Parser::new();
+        let mut results = [None; 64];
+
+        for length in [0_u16, 3, 5, 27, 29, 0x1C00, 0xFFFF] {
+            let [msb, lsb] = length.to_be_bytes();
+            let count = parse(&mut parser, &[0x42, 0x4D, msb, lsb], &mut results);
+            assert_eq!((count, results[0]), (1, Some(Err(FrameError::InvalidLength))), "length {}", length);
+        }
+
+        let count = parse(&mut parser, &data_frame(&measurement(9)), &mut results);
+        assert_eq!((count, results[0]), (1, Some(Ok(measurement(9)))));
+    }
+
+    #[test]
+    fn resynchronizes_after_garbage() {
+        let mut parser = Parser::new();
+        let mut results = [None; 64];
+
+        // Stray start characters and repeated first start character
+        let garbage = [0x00, 0x42, 0x42, 0x4D, 0x00, 0x42, 0xFF, 0x4D, 0x42];
+        assert_eq!(parse(&mut parser, &garbage, &mut results), 1);
+        assert_eq!(results[0], Some(Err(FrameError::InvalidLength)));
+
+        let count = parse(&mut parser, &data_frame(&measurement(3)), &mut results);
+        assert_eq!((count, results[0]), (1, Some(Ok(measurement(3)))));
+
+        // Truncated header, the start characters of the frame are taken
+        // as its length
+        let count = parse(&mut parser, &[0x42, 0x4D, 0x00], &mut results);
+        assert_eq!(count, 0);
+
+        let count = parse(&mut parser, &data_frame(&measurement(4)), &mut results);
+        assert_eq!(count, 2);
+        assert_eq!(results[0], Some(Err(FrameError::InvalidLength)));
+        assert_eq!(results[1], Some(Ok(measurement(4))));
+    }
+
+    #[test]
+    fn resynchronizes_inside_truncated_frame() {
+        let mut parser = Parser::new();
+        let mut results = [None; 64];
+        let truncated = data_frame(&measurement(1));
+        let frame = data_frame(&measurement(2));
+
+        // Transmission interrupted, the next frame starts within the
+        // bytes buffered for the first one
+        let mut bytes = [0; 20 + FRAME_LENGTH];
+        bytes[..20].copy_from_slice(&truncated[..20]);
+        bytes[20..].copy_from_slice(&frame);
+
+        let count = parse(&mut parser, &bytes, &mut results);
+        assert_eq!(count, 2);
+        assert_eq!(results[0], Some(Err(FrameError::ChecksumMismatch)));
+        assert_eq!(results[1], Some(Ok(measurement(2))));
+    }
+
+    #[test]
+    fn split_frame() {
+        let frame = data_frame(&measurement(4));
+
+        for split in 1..FRAME_LENGTH {
+            let mut parser = Parser::new();
+            let mut results = [None; 64];
+
+            assert_eq!(parse(&mut parser, &frame[..split], &mut results), 0, "split {}", split);
+            assert_eq!(parse(&mut parser, &frame[split..], &mut results), 1, "split {}", split);
+            assert_eq!(results[0], Some(Ok(measurement(4))), "split {}", split);
+        }
+    }
+
+    /// Linear congruential generator, deterministic pseudo-random bytes
+    struct Random(u32);
+
+    impl Random {
+        fn next(&mut self) -> u8 {
+            self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (self.0 >> 24) as u8
+        }
+    }
+
+    #[test]
+    fn random_garbage_between_frames() {
+        let mut random = Random(1);
+        let mut parser = Parser::new();
+
+        for round in 0..2000_u16 {
+            // Garbage biased towards the start characters and valid lengths
+            let garbage_length = random.next() % 48;
+
+            for _ in 0..garbage_length {
+                let byte = match random.next() % 8 {
+                    0 => 0x42,
+                    1 => 0x4D,
+                    2 => 0x00,
+                    3 => 0x1C,
+                    _ => random.next(),
+                };
+
+                if let Some(Ok(measurement)) = parser.push(byte) {
+                    panic!("round {} decoded garbage {:?}", round, measurement);
+                }
+            }
+
+            let expected = measurement(round % 600);
+            let decoded = data_frame(&expected).iter()
+                .filter_map(|&byte| parser.push(byte))
+                .filter(|result| result.is_ok())
+                .last();
+
+            assert_eq!(decoded, Some(Ok(expected)), "round {}", round);
+        }
+    }
+
+    #[test]
+    fn random_bytes() {
+        let mut random = Random(0x5EED);
+        let mut parser = Parser::new();
+
+        for _ in 0..100_000 {
+            if let Some(Ok(measurement)) = parser.push(random.next()) {
+                panic!("decoded noise {:?}", measurement);
+            }
+        }
+    }
+}
\ No newline at end of file
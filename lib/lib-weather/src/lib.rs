@@ -0,0 +1,13 @@
+#![no_std]
+
+mod sea_level;
+mod tendency;
+mod zambretti;
+mod psychrometrics;
+mod mold;
+
+pub use sea_level::sea_level_pressure;
+pub use tendency::{PressureHistory, PressureTendency, Trend};
+pub use zambretti::{zambretti, Forecast, WindDirection};
+pub use psychrometrics::{psychrometrics, Psychrometrics};
+pub use mold::MoldIndex;
\ No newline at end of file
@@ -0,0 +1,249 @@
+use libm::{expf, logf};
+
+const MAX_INDEX: f32 = 6.0;
+
+/// Longest time step integrated at once, longer gaps are not measured
+const MAX_STEP_MINUTES: u32 = 60;
+
+/// Packed state - index in millionths in the low bits, hours of
+/// unfavourable conditions above it and a marker of a valid state
+const INDEX_BITS: u32 = 23;
+const DRY_HOURS_BITS: u32 = 5;
+const MARKER: u32 = 0xA << 28;
+const MARKER_MASK: u32 = 0xF << 28;
+
+/// Mold growth index of a simplified VTT model (Hukka and Viitanen) for
+/// pine sapwood, 0 means no growth and 6 a surface fully covered by mold
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct MoldIndex {
+    index: f32,
+    /// Time since the conditions stopped being favourable for growth
+    dry_minutes: u32,
+}
+
+impl MoldIndex {
+    pub const fn new() -> Self {
+        Self { index: 0.0, dry_minutes: 0 }
+    }
+
+    /// Restore the state saved by `to_raw`, `None` for an invalid value
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        if raw & MARKER_MASK != MARKER {
+            return None;
+        }
+
+        let index = (raw & ((1 << INDEX_BITS) - 1)) as f32/1_000_000.0;
+        let dry_hours = (raw >> INDEX_BITS) & ((1 << DRY_HOURS_BITS) - 1);
+
+        Some(Self {
+            index: index.min(MAX_INDEX),
+            dry_minutes: dry_hours*60,
+        })
+    }
+
+    pub fn to_raw(&self) -> u32 {
+        let index = (self.index*1_000_000.0) as u32;
+        let dry_hours = (self.dry_minutes/60).min((1 << DRY_HOURS_BITS) - 1);
+        MARKER | (dry_hours << INDEX_BITS) | index
+    }
+
+    /// Index in 1/100
+    pub fn index(&self) -> u32 {
+        (self.index*100.0) as u32
+    }
+
+    /// Integrate the growth over `minutes` of `temperature` (in 1/100 degrees
+    /// celsius) and relative `humidity` (in 1/100 percent)
+    pub fn update(&mut self, temperature: i32, humidity: u32, minutes: u32) {
+        let minutes = minutes.min(MAX_STEP_MINUTES);
+        let temperature = temperature as f32/100.0;
+        let humidity = humidity.min(10000) as f32/100.0;
+        let critical = critical_humidity(temperature);
+
+        let favourable = temperature > 0.0 && temperature < 50.0
+            && humidity >= critical && critical < 100.0;
+
+        if favourable {
+            let growth = self.growth_per_day(temperature, humidity, critical);
+            self.index += growth*minutes as f32/1440.0;
+            self.dry_minutes = 0;
+        } else {
+            // Decline per hour depends on the time since growth stopped
+            let decline = match self.dry_minutes {
+                0..=359 => 0.032,
+                360..=1439 => 0.0,
+                _ => 0.016,
+            };
+
+            self.index -= decline*minutes as f32/60.0;
+            self.dry_minutes = self.dry_minutes.saturating_add(minutes);
+        }
+
+        self.index = self.index.clamp(0.0, MAX_INDEX);
+    }
+
+    fn growth_per_day(&self, temperature: f32, humidity: f32, critical: f32) -> f32 {
+        let ln_temperature = logf(temperature);
+        let ln_humidity = logf(humidity);
+
+        // Time to the start of growth and to visible mold in weeks
+        let start_weeks = expf(-0.68*ln_temperature - 13.9*ln_humidity + 66.02);
+        let visible_weeks = expf(-0.74*ln_temperature - 12.72*ln_humidity + 61.50);
+
+        let k1 = match self.index < 1.0 {
+            true => 1.0,
+            false => 2.0/(visible_weeks/start_weeks - 1.0),
+        };
+
+        let ratio = (critical - humidity)/(critical - 100.0);
+        let max_index = 1.0 + 7.0*ratio - 2.0*ratio*ratio;
+        let k2 = (1.0 - expf(2.3*(self.index - max_index))).max(0.0);
+
+        k1*k2/(7.0*start_weeks)
+    }
+}
+
+/// Relative humidity in percent above which mold grows
+fn critical_humidity(temperature: f32) -> f32 {
+    if temperature <= 20.0 {
+        let t = temperature;
+        -0.00267*t*t*t + 0.160*t*t - 3.13*t + 100.0
+    } else {
+        80.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Days of hourly updates at constant conditions, the index is sampled
+    /// at the end of every day
+    fn days(mold: &mut MoldIndex, days: u32, temperature: i32, humidity: u32) {
+        for _ in 0..24*days {
+            mold.update(temperature, humidity, 60);
+        }
+    }
+
+    fn assert_close(value: f32, expected: f32, tolerance: f32) {
+        assert!((value - expected).abs() <= tolerance, "{} is not {} +- {}", value, expected, tolerance);
+    }
+
+    #[test]
+    fn critical_humidity_curve() {
+        assert_close(critical_humidity(0.0), 100.0, 0.01);
+        assert_close(critical_humidity(10.0), 82.03, 0.01);
+        assert_close(critical_humidity(20.0), 80.04, 0.01);
+        assert_close(critical_humidity(20.5), 80.0, 0.01);
+        assert_close(critical_humidity(35.0), 80.0, 0.01);
+    }
+
+    #[test]
+    fn growth_at_20_degrees_and_95_percent() {
+        // Hukka and Viitanen give 1.98 weeks to the start of growth (index 1)
+        // and 3.89 weeks to visible mold (index 3) for these conditions
+        let mut mold = MoldIndex::new();
+
+        days(&mut mold, 13, 2000, 9500);
+        assert!(mold.index() < 100);
+        days(&mut mold, 1, 2000, 9500);
+        assert!(mold.index() >= 100);
+
+        days(&mut mold, 13, 2000, 9500);
+        assert!(mold.index() < 300);
+        days(&mut mold, 1, 2000, 9500);
+        assert!(mold.index() >= 300);
+
+        // Growth stops at the maximum index of the humidity, 5.1 here
+        days(&mut mold, 365, 2000, 9500);
+        assert_close(mold.index, 5.12, 0.01);
+    }
+
+    #[test]
+    fn no_growth_below_critical_humidity() {
+        let mut mold = MoldIndex::new();
+
+        days(&mut mold, 30, 2000, 7900);
+        days(&mut mold, 30, 1000, 8100);
+        days(&mut mold, 30, 0, 10000);
+        days(&mut mold, 30, -500, 10000);
+        days(&mut mold, 30, 5000, 10000);
+
+        assert_eq!(mold.index(), 0);
+    }
+
+    #[test]
+    fn decline_schedule() {
+        let mut mold = MoldIndex { index: 3.0, dry_minutes: 0 };
+
+        // Six hours of 0.032 per hour
+        for hour in 1..=6 {
+            mold.update(2000, 5000, 60);
+            assert_close(mold.index, 3.0 - 0.032*hour as f32, 0.0001);
+        }
+
+        // No decline until a day without growth
+        for _ in 6..24 {
+            mold.update(2000, 5000, 60);
+            assert_close(mold.index, 2.808, 0.0001);
+        }
+
+        // 0.016 per hour afterwards
+        for hour in 1..=10 {
+            mold.update(2000, 5000, 60);
+            assert_close(mold.index, 2.808 - 0.016*hour as f32, 0.0001);
+        }
+
+        // Favourable conditions restart the schedule
+        mold.update(2000, 9500, 60);
+        assert_eq!(mold.dry_minutes, 0);
+    }
+
+    #[test]
+    fn long_steps_are_limited() {
+        let mut mold = MoldIndex { index: 3.0, dry_minutes: 0 };
+        mold.update(2000, 5000, 600);
+
+        assert_close(mold.index, 3.0 - 0.032, 0.0001);
+        assert_eq!(mold.dry_minutes, 60);
+    }
+
+    #[test]
+    fn clamped_to_range() {
+        let mut mold = MoldIndex { index: 6.0, dry_minutes: 0 };
+        days(&mut mold, 10, 3000, 10000);
+        assert!(mold.index <= MAX_INDEX);
+
+        let mut mold = MoldIndex { index: 0.01, dry_minutes: 0 };
+        days(&mut mold, 10, 2000, 5000);
+        assert_eq!(mold.index, 0.0);
+
+        let raw = MARKER | 7_000_000;
+        assert_eq!(MoldIndex::from_raw(raw).unwrap().index, MAX_INDEX);
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        for (index, dry_minutes) in [(0.0, 0), (0.5, 120), (2.75, 600), (6.0, 1860)] {
+            let mold = MoldIndex { index, dry_minutes };
+            assert_eq!(MoldIndex::from_raw(mold.to_raw()), Some(mold));
+        }
+
+        // Dry time is stored in whole hours up to 31 hours
+        let mold = MoldIndex { index: 1.5, dry_minutes: 95 };
+        assert_eq!(MoldIndex::from_raw(mold.to_raw()).unwrap().dry_minutes, 60);
+
+        let mold = MoldIndex { index: 1.5, dry_minutes: 5000 };
+        assert_eq!(MoldIndex::from_raw(mold.to_raw()).unwrap().dry_minutes, 31*60);
+    }
+
+    #[test]
+    fn raw_without_marker() {
+        let raw = MoldIndex { index: 2.5, dry_minutes: 0 }.to_raw();
+
+        assert_eq!(MoldIndex::from_raw(raw & !MARKER_MASK), None);
+        // Backup register after the backup domain was reset
+        assert_eq!(MoldIndex::from_raw(0), None);
+        assert_eq!(MoldIndex::from_raw(u32::MAX), None);
+    }
+}
\ No newline at end of file
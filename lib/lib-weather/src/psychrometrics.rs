@@ -0,0 +1,174 @@
+use libm::{expf, logf, sqrtf, fabsf, roundf};
+
+/// Magnus formula coefficients over water (Sonntag 1990)
+const MAGNUS_B: f32 = 17.62;
+const MAGNUS_C: f32 = 243.12;
+
+/// Values derived from a temperature and relative humidity pair
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Psychrometrics {
+    /// In 1/100 degrees celsius
+    pub dew_point: i32,
+    /// In 1/100 grams of water vapour per cubic meter
+    pub absolute_humidity: u32,
+    /// Apparent temperature of the NOAA heat index in 1/100 degrees celsius
+    pub heat_index: i32,
+    /// Canadian humidex in 1/100
+    pub humidex: i32,
+}
+
+/// Derive the values from `temperature` in 1/100 degrees celsius
+/// and relative `humidity` in 1/100 percent
+pub fn psychrometrics(temperature: i32, humidity: u32) -> Psychrometrics {
+    let temperature = temperature as f32/100.0;
+    // Logarithm of zero humidity is not defined
+    let humidity = humidity.clamp(1, 10000) as f32/100.0;
+    let dew_point = dew_point(temperature, humidity);
+
+    Psychrometrics {
+        dew_point: hundredths(dew_point),
+        absolute_humidity: hundredths(absolute_humidity(temperature, humidity)) as u32,
+        heat_index: hundredths(heat_index(temperature, humidity)),
+        humidex: hundredths(humidex(temperature, dew_point)),
+    }
+}
+
+fn hundredths(value: f32) -> i32 {
+    roundf(value*100.0) as i32
+}
+
+fn dew_point(temperature: f32, humidity: f32) -> f32 {
+    let gamma = logf(humidity/100.0) + MAGNUS_B*temperature/(MAGNUS_C + temperature);
+    MAGNUS_C*gamma/(MAGNUS_B - gamma)
+}
+
+fn absolute_humidity(temperature: f32, humidity: f32) -> f32 {
+    let saturation = 6.112*expf(MAGNUS_B*temperature/(MAGNUS_C + temperature));
+    saturation*humidity*2.1674/(273.15 + temperature)
+}
+
+/// Rothfusz regression with the NOAA adjustments, the simple formula
+/// is used where the heat index is below 80 F
+fn heat_index(temperature: f32, humidity: f32) -> f32 {
+    let t = temperature*9.0/5.0 + 32.0;
+    let rh = humidity;
+    let simple = 0.5*(t + 61.0 + (t - 68.0)*1.2 + rh*0.094);
+
+    let fahrenheit = if (simple + t)/2.0 < 80.0 {
+        simple
+    } else {
+        let mut index = -42.379 + 2.049_015_3*t + 10.143_332*rh
+            - 0.224_755_4*t*rh - 0.006_837_83*t*t
+            - 0.054_817_17*rh*rh + 0.001_228_74*t*t*rh
+            + 0.000_852_82*t*rh*rh - 0.000_001_99*t*t*rh*rh;
+
+        if rh < 13.0 && (80.0..=112.0).contains(&t) {
+            index -= (13.0 - rh)/4.0*sqrtf((17.0 - fabsf(t - 95.0))/17.0);
+        } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+            index += (rh - 85.0)/10.0*(87.0 - t)/5.0;
+        }
+
+        index
+    };
+
+    (fahrenheit - 32.0)*5.0/9.0
+}
+
+fn humidex(temperature: f32, dew_point: f32) -> f32 {
+    let vapour_pressure = 6.11*expf(5417.753*(1.0/273.16 - 1.0/(273.15 + dew_point)));
+    temperature + 0.5555*(vapour_pressure - 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Value in 1/100 within `tolerance` of the expected value in units
+    fn assert_close(value: i32, expected: f32, tolerance: f32) {
+        let value = value as f32/100.0;
+        assert!((value - expected).abs() <= tolerance, "{} is not {} +- {}", value, expected, tolerance);
+    }
+
+    #[test]
+    fn dew_point_and_absolute_humidity() {
+        // Temperature, relative humidity, dew point and absolute humidity
+        let published = [
+            (2000, 5000, 9.26, 8.65),
+            (2500, 6000, 16.70, 13.82),
+            (3000, 8000, 26.17, 24.27),
+            (1000, 9000, 8.44, 8.49),
+            (3500, 2000, 8.69, 7.92),
+        ];
+
+        for (temperature, humidity, dew_point, absolute_humidity) in published {
+            let values = psychrometrics(temperature, humidity);
+
+            assert_close(values.dew_point, dew_point, 0.05);
+            assert_close(values.absolute_humidity as i32, absolute_humidity, 0.1);
+        }
+    }
+
+    #[test]
+    fn saturated_air() {
+        for temperature in [-2000, -500, 0, 1550, 2000, 4000] {
+            let values = psychrometrics(temperature, 10000);
+            assert!((values.dew_point - temperature).abs() <= 1);
+        }
+
+        // Over 100 percent is treated as saturated
+        assert_eq!(psychrometrics(2000, 12000), psychrometrics(2000, 10000));
+        assert_close(psychrometrics(0, 10000).absolute_humidity as i32, 4.85, 0.02);
+    }
+
+    #[test]
+    fn dry_air() {
+        let values = psychrometrics(2000, 0);
+
+        assert_eq!(values, psychrometrics(2000, 1));
+        assert!(values.dew_point < -7000);
+        assert_eq!(values.absolute_humidity, 0);
+    }
+
+    #[test]
+    fn below_zero() {
+        // Dew point over supercooled water
+        let values = psychrometrics(-1000, 8000);
+
+        assert_close(values.dew_point, -12.80, 0.05);
+        assert_close(values.absolute_humidity as i32, 1.89, 0.02);
+        assert!(values.dew_point < -1000);
+    }
+
+    #[test]
+    fn heat_index() {
+        // NOAA heat index table in fahrenheit
+        let published = [
+            (3222, 7000, 106.0),
+            (3500, 5000, 105.0),
+            (3000, 4000, 85.0),
+            (2667, 9000, 86.0),
+            (3778, 4000, 109.0),
+        ];
+
+        for (temperature, humidity, fahrenheit) in published {
+            let celsius = (fahrenheit - 32.0)*5.0/9.0;
+            assert_close(psychrometrics(temperature, humidity).heat_index, celsius, 0.6);
+        }
+
+        // Simple formula close to the air temperature in mild conditions
+        assert_close(psychrometrics(2000, 5000).heat_index, 19.4, 0.1);
+    }
+
+    #[test]
+    fn humidex() {
+        // Environment Canada humidex table by air temperature and dew point
+        assert_eq!(roundf(super::humidex(30.0, 15.0)), 34.0);
+        assert_eq!(roundf(super::humidex(35.0, 25.0)), 47.0);
+        assert_eq!(roundf(super::humidex(25.0, 20.0)), 33.0);
+        assert_eq!(roundf(super::humidex(40.0, 10.0)), 41.0);
+
+        // Dry air feels cooler than the air temperature
+        assert!(psychrometrics(2500, 1000).humidex < 2500);
+        assert_close(psychrometrics(3000, 8000).humidex, 43.6, 0.3);
+    }
+}
\ No newline at end of file
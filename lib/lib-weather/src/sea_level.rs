@@ -0,0 +1,92 @@
+/// Fraction bits of the fixed point numbers
+const FRACTION_BITS: u32 = 30;
+const ONE: i64 = 1 << FRACTION_BITS;
+
+/// Temperature lapse rate divided by the sea level temperature of the
+/// standard atmosphere (0.0065 K/m / 288.15 K) in 1/10^12 per meter
+const LAPSE_RATIO_PICO: i64 = 22_557_700;
+
+/// Exponent g*M/(R*L) of the barometric formula in 1/10^5
+const EXPONENT: i64 = 525_588;
+
+/// Reduce station `pressure` (in pascals) measured at `altitude` (in meters)
+/// to the sea level using the standard atmosphere, the same way as QNH
+pub fn sea_level_pressure(pressure: i32, altitude: i32) -> i32 {
+    // p0 = p*(1 - x)^-exponent = p*exp(-exponent*ln(1 - x))
+    let x = (altitude as i128*(LAPSE_RATIO_PICO*ONE) as i128/1_000_000_000_000) as i64;
+    let y = -multiply(EXPONENT*ONE/100_000, ln_one_minus(x));
+    ((pressure as i64*exp(y) + ONE/2) >> FRACTION_BITS) as i32
+}
+
+fn multiply(a: i64, b: i64) -> i64 {
+    ((a as i128*b as i128) >> FRACTION_BITS) as i64
+}
+
+/// ln(1 - x) as a power series, converges quickly for altitudes up to
+/// several kilometers where x stays below 0.1
+fn ln_one_minus(x: i64) -> i64 {
+    let mut power = x;
+    let mut sum = 0;
+
+    for n in 1..=8 {
+        sum -= power/n;
+        power = multiply(power, x);
+    }
+
+    sum
+}
+
+fn exp(y: i64) -> i64 {
+    let mut term = ONE;
+    let mut sum = ONE;
+
+    for n in 1..=12 {
+        term = multiply(term, y)/n;
+        sum += term;
+    }
+
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    /// Barometric formula of the standard atmosphere in floating point
+    fn standard_atmosphere(pressure: i32, altitude: i32) -> f64 {
+        pressure as f64*(1.0 - 0.0065*altitude as f64/288.15).powf(-5.25588)
+    }
+
+    #[test]
+    fn matches_standard_atmosphere() {
+        for altitude in [-400, -100, 0, 1, 100, 250, 500, 1000, 2000, 3000] {
+            for pressure in [70_000, 85_000, 95_000, 101_325, 105_000] {
+                let expected = standard_atmosphere(pressure, altitude);
+                let reduced = sea_level_pressure(pressure, altitude);
+
+                assert!(
+                    (reduced as f64 - expected).abs() <= 1.0,
+                    "{} Pa at {} m gives {} instead of {}", pressure, altitude, reduced, expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sea_level_is_unchanged() {
+        for pressure in [0, 95_000, 101_325, 105_000] {
+            assert_eq!(sea_level_pressure(pressure, 0), pressure);
+        }
+    }
+
+    #[test]
+    fn standard_pressure_at_altitude() {
+        // Standard atmosphere pressure at 1000 m and 2000 m
+        assert!((sea_level_pressure(89_875, 1000) - 101_325).abs() <= 2);
+        assert!((sea_level_pressure(79_495, 2000) - 101_325).abs() <= 2);
+        // Below the sea level the station pressure is higher
+        assert!(sea_level_pressure(101_325, -400) < 101_325);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,182 @@
+/// Interval of the pressure tendency as used by weather services
+const TENDENCY_MINUTES: i32 = 180;
+
+/// Pressure change in pascals per 3 hours below which pressure is steady
+const STEADY_LIMIT: i32 = 160;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Trend {
+    Rising,
+    Steady,
+    Falling,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct PressureTendency {
+    /// Pressure change in pascals per 3 hours
+    pub change: i32,
+    pub trend: Trend,
+}
+
+/// Ring buffer of `N` pressure samples taken every `interval` minutes, the
+/// tendency is available once the buffer covers the whole `(N - 1)*interval`
+pub struct PressureHistory<const N: usize> {
+    interval: u32,
+    samples: [i32; N],
+    count: usize,
+    newest: usize,
+    newest_time: u32,
+}
+
+impl<const N: usize> PressureHistory<N> {
+    pub const fn new(interval: u32) -> Self {
+        Self {
+            interval,
+            samples: [0; N],
+            count: 0,
+            newest: 0,
+            newest_time: 0,
+        }
+    }
+
+    /// Record `pressure` (in pascals) measured at `time` (monotonic minutes),
+    /// a sample is stored once per interval, the history is restarted after
+    /// a gap in the measurements or when the clock goes back
+    pub fn record(&mut self, time: u32, pressure: i32) {
+        if self.count > 0 {
+            if time < self.newest_time || time >= self.newest_time + 2*self.interval {
+                self.count = 0;
+            } else if time < self.newest_time + self.interval {
+                return;
+            }
+        }
+
+        self.newest = (self.newest + 1) % N;
+        self.samples[self.newest] = pressure;
+        self.newest_time = time;
+        self.count = (self.count + 1).min(N);
+    }
+
+    pub fn tendency(&self) -> Option<PressureTendency> {
+        if self.count < N || N < 2 {
+            return None;
+        }
+
+        let oldest = (self.newest + 1) % N;
+        let span = ((N - 1) as u32*self.interval) as i32;
+        let change = (self.samples[self.newest] - self.samples[oldest])*TENDENCY_MINUTES/span;
+
+        let trend = match change {
+            change if change >= STEADY_LIMIT => Trend::Rising,
+            change if change <= -STEADY_LIMIT => Trend::Falling,
+            _ => Trend::Steady,
+        };
+
+        Some(PressureTendency { change, trend })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Four hourly samples span the three hours of the tendency
+    fn history(pressures: &[i32]) -> PressureHistory<4> {
+        let mut history = PressureHistory::new(60);
+
+        for (hour, pressure) in pressures.iter().enumerate() {
+            history.record(1000 + 60*hour as u32, *pressure);
+        }
+
+        history
+    }
+
+    fn tendency(change: i32, trend: Trend) -> Option<PressureTendency> {
+        Some(PressureTendency { change, trend })
+    }
+
+    #[test]
+    fn trend_thresholds() {
+        assert_eq!(history(&[101_000, 101_050, 101_100, 101_160]).tendency(), tendency(160, Trend::Rising));
+        assert_eq!(history(&[101_000, 101_050, 101_100, 101_159]).tendency(), tendency(159, Trend::Steady));
+        assert_eq!(history(&[101_000, 101_000, 101_000, 101_000]).tendency(), tendency(0, Trend::Steady));
+        assert_eq!(history(&[101_000, 100_950, 100_900, 100_841]).tendency(), tendency(-159, Trend::Steady));
+        assert_eq!(history(&[101_000, 100_950, 100_900, 100_840]).tendency(), tendency(-160, Trend::Falling));
+    }
+
+    #[test]
+    fn change_between_oldest_and_newest() {
+        // Only the ends of the window count, samples roll out of the buffer
+        let history = history(&[99_000, 101_000, 100_000, 102_000, 101_300]);
+        assert_eq!(history.tendency(), tendency(300, Trend::Rising));
+    }
+
+    #[test]
+    fn change_scaled_to_three_hours() {
+        let mut history = PressureHistory::<3>::new(30);
+        history.record(0, 101_000);
+        history.record(30, 101_040);
+        history.record(60, 101_100);
+
+        assert_eq!(history.tendency(), tendency(300, Trend::Rising));
+    }
+
+    #[test]
+    fn short_history() {
+        assert_eq!(history(&[]).tendency(), None);
+        assert_eq!(history(&[101_000]).tendency(), None);
+        assert_eq!(history(&[101_000, 101_100, 101_200]).tendency(), None);
+        assert_eq!(PressureHistory::<1>::new(60).tendency(), None);
+    }
+
+    #[test]
+    fn samples_within_interval_are_skipped() {
+        let mut history = PressureHistory::<4>::new(60);
+
+        for minute in 0..=180 {
+            // Only the samples at whole hours are stored
+            let pressure = match minute % 60 {
+                0 => 101_000 + minute as i32,
+                _ => 90_000,
+            };
+
+            history.record(minute, pressure);
+        }
+
+        assert_eq!(history.tendency(), tendency(180, Trend::Rising));
+    }
+
+    #[test]
+    fn late_samples_keep_the_history() {
+        let mut history = history(&[101_000, 101_100, 101_200]);
+        history.record(1000 + 3*60 + 59, 101_300);
+
+        assert_eq!(history.tendency(), tendency(300, Trend::Rising));
+    }
+
+    #[test]
+    fn gap_restarts_the_history() {
+        let mut history = history(&[101_000, 101_100, 101_200, 101_300]);
+        history.record(1000 + 3*60 + 120, 101_400);
+        assert_eq!(history.tendency(), None);
+
+        for hour in 1..=3 {
+            history.record(1000 + 3*60 + 120 + 60*hour, 101_400);
+        }
+
+        assert_eq!(history.tendency(), tendency(0, Trend::Steady));
+    }
+
+    #[test]
+    fn clock_going_back_restarts_the_history() {
+        let mut history = history(&[101_000, 101_100, 101_200, 101_300]);
+        history.record(500, 101_300);
+        assert_eq!(history.tendency(), None);
+
+        for hour in 1..=3 {
+            history.record(500 + 60*hour, 101_300 - 100*hour as i32);
+        }
+
+        assert_eq!(history.tendency(), tendency(-300, Trend::Falling));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,205 @@
+use crate::tendency::Trend;
+
+/// Pressure range of the forecast in pascals, lower and upper bound
+const PRESSURE_BOTTOM: i32 = 95_000;
+const PRESSURE_RANGE: i32 = 10_000;
+
+/// Number of pressure bands of the forecast tables
+const BANDS: i32 = 22;
+
+/// Pressure shift of a rising trend in summer and a falling trend in winter
+const SEASON_ADJUSTMENT: i32 = PRESSURE_RANGE*7/100;
+
+/// Forecast (as an index of `FORECASTS`) for every pressure band from
+/// the lowest, depending on the pressure trend
+const RISING_FORECASTS: [u8; BANDS as usize] =
+    [25, 25, 25, 24, 24, 19, 16, 12, 11, 9, 8, 6, 5, 2, 1, 1, 0, 0, 0, 0, 0, 0];
+const STEADY_FORECASTS: [u8; BANDS as usize] =
+    [25, 25, 25, 25, 25, 25, 23, 23, 22, 18, 15, 13, 10, 4, 1, 1, 0, 0, 0, 0, 0, 0];
+const FALLING_FORECASTS: [u8; BANDS as usize] =
+    [25, 25, 25, 25, 25, 25, 25, 25, 23, 23, 21, 20, 17, 14, 7, 3, 1, 1, 1, 0, 0, 0];
+
+const FORECASTS: [&str; 26] = [
+    "Settled fine",
+    "Fine weather",
+    "Becoming fine",
+    "Fine, becoming less settled",
+    "Fine, possible showers",
+    "Fairly fine, improving",
+    "Fairly fine, possible showers early",
+    "Fairly fine, showery later",
+    "Showery early, improving",
+    "Changeable, mending",
+    "Fairly fine, showers likely",
+    "Rather unsettled clearing later",
+    "Unsettled, probably improving",
+    "Showery, bright intervals",
+    "Showery, becoming less settled",
+    "Changeable, some rain",
+    "Unsettled, short fine intervals",
+    "Unsettled, rain later",
+    "Unsettled, some rain",
+    "Mostly very unsettled",
+    "Occasional rain, worsening",
+    "Rain at times, very unsettled",
+    "Rain at frequent intervals",
+    "Rain, very unsettled",
+    "Stormy, may improve",
+    "Stormy, much rain",
+];
+
+/// Wind direction on the 16 point compass rose
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum WindDirection {
+    N, NNE, NE, ENE, E, ESE, SE, SSE, S, SSW, SW, WSW, W, WNW, NW, NNW,
+}
+
+impl WindDirection {
+    /// Pressure adjustment in pascals, northern winds bring fair weather
+    fn adjustment(self) -> i32 {
+        // In 1/10 percent of the pressure range
+        let permille = match self {
+            WindDirection::N => 60,
+            WindDirection::NNE => 50,
+            WindDirection::NE => 50,
+            WindDirection::ENE => 20,
+            WindDirection::E => -5,
+            WindDirection::ESE => -20,
+            WindDirection::SE => -50,
+            WindDirection::SSE => -85,
+            WindDirection::S => -120,
+            WindDirection::SSW => -100,
+            WindDirection::SW => -60,
+            WindDirection::WSW => -45,
+            WindDirection::W => -30,
+            WindDirection::WNW => -5,
+            WindDirection::NW => 15,
+            WindDirection::NNW => 30,
+        };
+
+        PRESSURE_RANGE*permille/1000
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Forecast {
+    /// Forecast index, 0 is `A` (settled fine) and 25 is `Z` (stormy)
+    pub code: u8,
+    /// Pressure is out of the range of the forecast tables
+    pub exceptional: bool,
+}
+
+impl Forecast {
+    pub fn letter(&self) -> char {
+        (b'A' + self.code) as char
+    }
+
+    pub fn text(&self) -> &'static str {
+        FORECASTS[self.code as usize]
+    }
+}
+
+/// Zambretti forecast for the northern hemisphere from the sea level
+/// `pressure` (in pascals), its 3 hour `trend`, the `month` (1-12) and
+/// the wind direction when known
+pub fn zambretti(
+    pressure: i32,
+    trend: Trend,
+    month: u8,
+    wind: Option<WindDirection>,
+) -> Forecast {
+    let summer = (4..=9).contains(&month);
+
+    let pressure = pressure + wind.map_or(0, WindDirection::adjustment) + match trend {
+        Trend::Rising if summer => SEASON_ADJUSTMENT,
+        Trend::Falling if !summer => -SEASON_ADJUSTMENT,
+        _ => 0,
+    };
+
+    // Top of the range still belongs to the highest band
+    let pressure = if pressure == PRESSURE_BOTTOM + PRESSURE_RANGE { pressure - 1 } else { pressure };
+
+    let band = (pressure - PRESSURE_BOTTOM)*BANDS;
+    let band = band.div_euclid(PRESSURE_RANGE);
+    let exceptional = !(0..BANDS).contains(&band);
+    let band = band.clamp(0, BANDS - 1) as usize;
+
+    let code = match trend {
+        Trend::Rising => RISING_FORECASTS[band],
+        Trend::Steady => STEADY_FORECASTS[band],
+        Trend::Falling => FALLING_FORECASTS[band],
+    };
+
+    Forecast { code, exceptional }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expected letters are worked out by hand with the Beteljuice
+    /// version of the algorithm, pressures are in hectopascals
+    fn letter(hectopascals: i32, trend: Trend, month: u8, wind: Option<WindDirection>) -> char {
+        zambretti(hectopascals*100, trend, month, wind).letter()
+    }
+
+    #[test]
+    fn steady_pressure_is_not_adjusted_in_any_season() {
+        assert_eq!(letter(1020, Trend::Steady, 1, None), 'B');
+        assert_eq!(letter(1020, Trend::Steady, 7, None), 'B');
+        assert_eq!(zambretti(102_000, Trend::Steady, 1, None).text(), "Fine weather");
+    }
+
+    #[test]
+    fn rising_trend_is_adjusted_in_summer_only() {
+        // 1027 hPa in summer, band 16
+        assert_eq!(letter(1020, Trend::Rising, 7, None), 'A');
+        // 1000 hPa in winter, band 11
+        assert_eq!(letter(1000, Trend::Rising, 1, None), 'G');
+        // 1007 hPa in summer, band 12
+        assert_eq!(letter(1000, Trend::Rising, 7, None), 'F');
+    }
+
+    #[test]
+    fn falling_trend_is_adjusted_in_winter_only() {
+        // 993 hPa in winter, band 9
+        assert_eq!(letter(1000, Trend::Falling, 1, None), 'X');
+        assert_eq!(zambretti(100_000, Trend::Falling, 1, None).text(), "Rain, very unsettled");
+        // 1000 hPa in summer, band 11
+        assert_eq!(letter(1000, Trend::Falling, 7, None), 'U');
+    }
+
+    #[test]
+    fn season_boundaries() {
+        assert_eq!(letter(1000, Trend::Falling, 3, None), 'X');
+        assert_eq!(letter(1000, Trend::Falling, 4, None), 'U');
+        assert_eq!(letter(1000, Trend::Falling, 9, None), 'U');
+        assert_eq!(letter(1000, Trend::Falling, 10, None), 'X');
+    }
+
+    #[test]
+    fn wind_direction_shifts_the_pressure() {
+        // 988 hPa with the southern wind, band 8
+        assert_eq!(letter(1000, Trend::Steady, 1, Some(WindDirection::S)), 'W');
+        // 1006 hPa with the northern wind, band 12
+        assert_eq!(letter(1000, Trend::Steady, 1, Some(WindDirection::N)), 'K');
+    }
+
+    #[test]
+    fn pressure_out_of_range_is_exceptional() {
+        let low = zambretti(94_000, Trend::Steady, 1, None);
+        assert!(low.exceptional);
+        assert_eq!(low.letter(), 'Z');
+
+        let high = zambretti(106_000, Trend::Steady, 1, None);
+        assert!(high.exceptional);
+        assert_eq!(high.letter(), 'A');
+    }
+
+    #[test]
+    fn range_bounds_are_not_exceptional() {
+        assert!(!zambretti(95_000, Trend::Steady, 1, None).exceptional);
+        assert!(!zambretti(105_000, Trend::Steady, 1, None).exceptional);
+        assert_eq!(letter(1050, Trend::Falling, 7, None), 'A');
+    }
+}
\ No newline at end of file
@@ -1,7 +1,8 @@
 MEMORY
 {
   /* NOTE K = KiBi = 1024 bytes */
-  FLASH : ORIGIN = 0x08000000, LENGTH = 512K
+  /* Last 128K sector (sector 7) keeps the sensor calibration */
+  FLASH : ORIGIN = 0x08000000, LENGTH = 384K
   RAM : ORIGIN = 0x20000000, LENGTH = 128K
 }
 